@@ -0,0 +1,370 @@
+use crate::drawable::DrawType;
+use crate::paint::PixelColor;
+use crate::Graphics;
+use graphics_shapes::prelude::*;
+use graphics_shapes::shape_box::ShapeBox;
+use ici_files::prelude::*;
+use std::mem::swap;
+
+/// Distance from `point` to the line segment `start`-`end`
+pub(crate) fn distance_to_segment(point: (f32, f32), start: (f32, f32), end: (f32, f32)) -> f32 {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq == 0.0 {
+        0.0
+    } else {
+        (((point.0 - start.0) * dx + (point.1 - start.1) * dy) / len_sq).clamp(0.0, 1.0)
+    };
+    (start.0 + t * dx - point.0).hypot(start.1 + t * dy - point.1)
+}
+
+/// Even-odd test for `point` inside `points`
+fn polygon_contains(points: &[(f32, f32)], point: (f32, f32)) -> bool {
+    let mut inside = false;
+    let mut j = points.len() - 1;
+    for i in 0..points.len() {
+        let (a, b) = (points[i], points[j]);
+        if (a.1 > point.1) != (b.1 > point.1)
+            && point.0 < (b.0 - a.0) * (point.1 - a.1) / (b.1 - a.1) + a.0
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+impl Graphics<'_> {
+    /// True if lines and shapes are drawn with anti-aliasing
+    #[inline(always)]
+    pub fn anti_aliasing(&self) -> bool {
+        self.anti_aliasing
+    }
+
+    /// Set whether lines, arcs, polylines, circles, ellipses, triangles and polygons are drawn with
+    /// smooth edges, partially covered pixels are blended using the current [BlendMode](crate::blending::BlendMode)
+    ///
+    /// Rects (unless transformed), rotated ellipses, text and images aren't affected
+    ///
+    /// # Returns
+    /// The previous setting
+    #[inline]
+    pub fn set_anti_aliasing(&mut self, anti_aliasing: bool) -> bool {
+        let old = self.anti_aliasing;
+        self.anti_aliasing = anti_aliasing;
+        old
+    }
+
+    /// Run `method` with anti-aliasing set to `anti_aliasing`, the previous setting is restored afterwards
+    #[inline]
+    pub fn with_anti_aliasing<F: Fn(&mut Graphics)>(&mut self, anti_aliasing: bool, method: F) {
+        let old = self.set_anti_aliasing(anti_aliasing);
+        method(self);
+        self.set_anti_aliasing(old);
+    }
+
+    /// Update the pixel at `x`, `y` with `color` with its alpha scaled by `coverage` (0.0-1.0)
+    #[inline]
+    fn plot_coverage(&mut self, x: isize, y: isize, color: Color, coverage: f32) {
+        let alpha = (color.a as f32 * coverage.clamp(0.0, 1.0)).round() as u8;
+        if alpha > 0 {
+            self.set_pixel(x, y, color.with_alpha(alpha));
+        }
+    }
+
+    /// Draw a line using Xiaolin Wu's algorithm, the points must already be transformed
+    pub(crate) fn plot_line_aa(&mut self, start: Coord, end: Coord, color: Color) {
+        let (mut x0, mut y0) = (start.x as f32, start.y as f32);
+        let (mut x1, mut y1) = (end.x as f32, end.y as f32);
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            swap(&mut x0, &mut y0);
+            swap(&mut x1, &mut y1);
+        }
+        if x0 > x1 {
+            swap(&mut x0, &mut x1);
+            swap(&mut y0, &mut y1);
+        }
+        let dx = x1 - x0;
+        let gradient = if dx == 0.0 { 0.0 } else { (y1 - y0) / dx };
+        let mut y = y0;
+        for x in (x0 as isize)..=(x1 as isize) {
+            let base = y.floor();
+            let fraction = y - base;
+            let base = base as isize;
+            if steep {
+                self.plot_coverage(base, x, color, 1.0 - fraction);
+                self.plot_coverage(base + 1, x, color, fraction);
+            } else {
+                self.plot_coverage(x, base, color, 1.0 - fraction);
+                self.plot_coverage(x, base + 1, color, fraction);
+            }
+            y += gradient;
+        }
+    }
+
+    /// Draw the arc from `angle_start` to `angle_end` (degrees, clockwise from the top) with
+    /// anti-aliasing, the arc isn't transformed
+    pub(crate) fn plot_arc_aa(
+        &mut self,
+        center: Coord,
+        angle_start: isize,
+        angle_end: isize,
+        radius: usize,
+        color: Color,
+    ) {
+        if angle_end < angle_start {
+            return;
+        }
+        let sweep = (angle_end - angle_start) as f32;
+        let start = angle_start as f32;
+        let radius_f = radius as f32;
+        // half a pixel past each end, so the end pixels match the aliased arc
+        let tolerance = if radius == 0 {
+            360.0
+        } else {
+            (0.5 / radius_f).to_degrees()
+        };
+        let bounds = (center, radius as isize, radius as isize);
+        self.plot_coverage_map(bounds, &PixelColor::Solid(color), |x, y| {
+            let (dx, dy) = ((x - center.x) as f32, (y - center.y) as f32);
+            let coverage = 1.0 - (dx.hypot(dy) - radius_f).abs();
+            if coverage <= 0.0 || sweep >= 360.0 {
+                return coverage;
+            }
+            let angle = (dy.atan2(dx).to_degrees() + 90.0 - start).rem_euclid(360.0);
+            if angle <= sweep + tolerance || angle >= 360.0 - tolerance {
+                coverage
+            } else {
+                0.0
+            }
+        });
+    }
+
+    /// Draw `shape` with anti-aliasing, the shape must already be transformed
+    ///
+    /// Returns false (without drawing anything) if the shape isn't supported, i.e. rects and
+    /// rotated ellipses
+    pub(crate) fn draw_anti_aliased(&mut self, shape: &ShapeBox, draw_type: &DrawType) -> bool {
+        let color = draw_type.color();
+        let stroke = draw_type.is_stroke();
+        let transform = self.transform;
+        let pixel_color = PixelColor::new(draw_type, &transform, self.translate);
+        match shape {
+            ShapeBox::Rect(_) => return false,
+            ShapeBox::Ellipse(ellipse) if ellipse.angle() != 0 => return false,
+            ShapeBox::Line(line) => self.plot_line_aa(line.start(), line.end(), color),
+            ShapeBox::Circle(circle) => {
+                let center = circle.center();
+                let radius = circle.radius() as f32;
+                let bounds = (center, circle.radius() as isize, circle.radius() as isize);
+                self.plot_coverage_map(bounds, &pixel_color, |x, y| {
+                    let distance = ((x - center.x) as f32).hypot((y - center.y) as f32);
+                    if stroke {
+                        1.0 - (distance - radius).abs()
+                    } else {
+                        radius + 1.0 - distance
+                    }
+                });
+            }
+            ShapeBox::Ellipse(ellipse) => {
+                let center = ellipse.center();
+                let a = (ellipse.width() / 2) as f32;
+                let b = (ellipse.height() / 2) as f32;
+                if a == 0.0 || b == 0.0 {
+                    return false;
+                }
+                let bounds = (center, a as isize, b as isize);
+                self.plot_coverage_map(bounds, &pixel_color, |x, y| {
+                    let (x, y) = ((x - center.x) as f32, (y - center.y) as f32);
+                    let value = (x * x) / (a * a) + (y * y) / (b * b);
+                    let gradient = (2.0 * x / (a * a)).hypot(2.0 * y / (b * b));
+                    // approximate distance to the edge, negative inside
+                    let distance = if gradient == 0.0 {
+                        -a.min(b)
+                    } else {
+                        (value - 1.0) / gradient
+                    };
+                    if stroke {
+                        1.0 - distance.abs()
+                    } else {
+                        1.0 - distance
+                    }
+                });
+            }
+            ShapeBox::Triangle(_) | ShapeBox::Polygon(_) => {
+                let coords = shape.points();
+                let points: Vec<(f32, f32)> =
+                    coords.iter().map(|p| (p.x as f32, p.y as f32)).collect();
+                if points.is_empty() {
+                    return true;
+                }
+                let left = coords.iter().map(|p| p.x).min().unwrap_or_default();
+                let right = coords.iter().map(|p| p.x).max().unwrap_or_default();
+                let top = coords.iter().map(|p| p.y).min().unwrap_or_default();
+                let bottom = coords.iter().map(|p| p.y).max().unwrap_or_default();
+                let bounds = (
+                    Coord::new((left + right) / 2, (top + bottom) / 2),
+                    (right - left) / 2 + 1,
+                    (bottom - top) / 2 + 1,
+                );
+                self.plot_coverage_map(bounds, &pixel_color, |x, y| {
+                    let point = (x as f32, y as f32);
+                    if !stroke && polygon_contains(&points, point) {
+                        return 1.0;
+                    }
+                    let distance = points
+                        .iter()
+                        .zip(points.iter().cycle().skip(1))
+                        .map(|(start, end)| distance_to_segment(point, *start, *end))
+                        .fold(f32::MAX, f32::min);
+                    1.0 - distance
+                });
+            }
+        }
+        true
+    }
+
+    /// Call `coverage` for every visible pixel within `half_width`, `half_height` (plus 1 for the
+    /// smooth edge) of `center` and draw the pixel's `color` with that coverage
+    fn plot_coverage_map<F: Fn(isize, isize) -> f32>(
+        &mut self,
+        (center, half_width, half_height): (Coord, isize, isize),
+        color: &PixelColor,
+        coverage: F,
+    ) {
+        // only check pixels inside the buffer
+        let offset = self.origin - self.translate;
+        let left = (center.x - half_width - 1).max(offset.x);
+        let right = (center.x + half_width + 1).min(offset.x + self.width as isize - 1);
+        let top = (center.y - half_height - 1).max(offset.y);
+        let bottom = (center.y + half_height + 1).min(offset.y + self.height as isize - 1);
+        for y in top..=bottom {
+            for x in left..=right {
+                self.plot_coverage(x, y, color.at(x, y), coverage(x, y));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::make_image;
+    use crate::prelude::*;
+
+    fn alpha(image: &Image, x: usize, y: usize) -> u8 {
+        image.get_pixel(x, y).a
+    }
+
+    #[test]
+    fn line_coverage() {
+        let image = make_image(6, 4, |g| {
+            g.set_anti_aliasing(true);
+            g.draw_line((0, 0), (4, 2), RED);
+        })
+        .unwrap();
+        assert_eq!(alpha(&image, 0, 0), 255);
+        assert_eq!(alpha(&image, 2, 1), 255);
+        assert_eq!(alpha(&image, 1, 0), 128);
+        assert_eq!(alpha(&image, 1, 1), 128);
+        assert_eq!(alpha(&image, 4, 2), 255);
+        assert_eq!(alpha(&image, 5, 2), 0);
+        assert_eq!(image.get_pixel(1, 1).r, 255);
+
+        let straight = make_image(6, 4, |g| {
+            g.set_anti_aliasing(true);
+            g.draw_line((0, 1), (5, 1), RED);
+        })
+        .unwrap();
+        let aliased = make_image(6, 4, |g| g.draw_line((0, 1), (5, 1), RED)).unwrap();
+        assert_eq!(straight, aliased);
+    }
+
+    #[test]
+    fn shapes_are_smooth() {
+        let size = 21;
+        let draw = |draw_type: DrawType, anti_aliasing: bool| {
+            make_image(size, size, |g| {
+                g.set_anti_aliasing(anti_aliasing);
+                g.draw_circle(Circle::new((10, 10), 7), draw_type);
+            })
+            .unwrap()
+        };
+        for draw_type in [fill(BLUE), stroke(BLUE)] {
+            let smooth = draw(draw_type.clone(), true);
+            let aliased = draw(draw_type.clone(), false);
+            let partial = smooth
+                .pixels()
+                .iter()
+                .filter(|c| c.a > 0 && c.a < 255)
+                .count();
+            assert!(partial > 0, "{draw_type:?}");
+            assert_eq!(smooth.get_pixel(10, 3).a, 255, "{draw_type:?}");
+            assert_eq!(smooth.get_pixel(0, 0), TRANSPARENT);
+            let covered =
+                |image: &Image| image.pixels().iter().map(|c| c.a as usize).sum::<usize>();
+            let difference = covered(&smooth).abs_diff(covered(&aliased));
+            assert!(difference < covered(&aliased) / 5, "{draw_type:?}");
+        }
+        assert_eq!(draw(fill(BLUE), true).get_pixel(10, 10).a, 255);
+        assert_eq!(draw(stroke(BLUE), true).get_pixel(10, 10).a, 0);
+
+        let polygon = make_image(size, size, |g| {
+            g.set_anti_aliasing(true);
+            g.draw_triangle(Triangle::new((2, 2), (18, 6), (6, 18)), fill(GREEN));
+            g.draw_ellipse(Ellipse::new((10, 10), 8, 4), stroke(RED));
+        })
+        .unwrap();
+        assert_eq!(polygon.get_pixel(2, 2).a, 255);
+        assert_eq!(polygon.get_pixel(6, 6), GREEN);
+        assert!(polygon.pixels().iter().any(|c| c.a > 0 && c.a < 255));
+    }
+
+    #[test]
+    fn arc_coverage() {
+        let draw = |anti_aliasing: bool| {
+            make_image(20, 20, |g| {
+                g.set_anti_aliasing(anti_aliasing);
+                g.draw_arc(coord!(10, 10), 0, 90, 8, false, RED);
+            })
+            .unwrap()
+        };
+        let (smooth, aliased) = (draw(true), draw(false));
+        assert_ne!(smooth, aliased);
+        assert_eq!(alpha(&smooth, 10, 2), 255);
+        assert_eq!(alpha(&smooth, 18, 10), 255);
+        assert!(smooth.pixels().iter().any(|c| c.a > 0 && c.a < 255));
+        for y in 0..20 {
+            for x in 0..20 {
+                if x < 9 || y > 11 {
+                    assert_eq!(alpha(&smooth, x, y), 0, "{x},{y}");
+                }
+            }
+        }
+
+        let polyline = |anti_aliasing: bool| {
+            make_image(20, 20, |g| {
+                g.set_anti_aliasing(anti_aliasing);
+                Polyline::start((10, 2), RED)
+                    .add_arc_around((10, 10), 8, 0, 90)
+                    .unwrap()
+                    .render(g);
+            })
+            .unwrap()
+        };
+        assert_eq!(polyline(true), smooth);
+        assert_eq!(polyline(false), aliased);
+    }
+
+    #[test]
+    fn saved_in_state() {
+        let mut buffer = Graphics::create_buffer_u8(2, 2);
+        let mut graphics = Graphics::new_u8_rgba(&mut buffer, 2, 2).unwrap();
+        graphics.save();
+        graphics.set_anti_aliasing(true);
+        graphics.with_anti_aliasing(false, |g| assert!(!g.anti_aliasing()));
+        assert!(graphics.anti_aliasing());
+        graphics.restore();
+        assert!(!graphics.anti_aliasing());
+    }
+}
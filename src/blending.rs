@@ -0,0 +1,256 @@
+use ici_files::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How a color being drawn is combined with the color already in the buffer
+///
+/// All modes respect the alpha of the color being drawn, i.e. drawing with `Multiply` and a color
+/// with alpha 128 will produce a result half way between the existing color and the multiplied color
+///
+/// Set with [Graphics::set_blend_mode](crate::Graphics::set_blend_mode)
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum BlendMode {
+    /// Standard alpha blending, opaque colors replace the existing color
+    #[default]
+    Normal,
+    /// Darkens by multiplying channels, white has no effect
+    Multiply,
+    /// Lightens by adding channels, black has no effect
+    Additive,
+    /// Lightens by inverting, multiplying and inverting again, black has no effect
+    Screen,
+    /// Multiply for dark areas and screen for light areas of the existing color
+    Overlay,
+    /// Keeps the darker of each channel
+    Darken,
+    /// Keeps the lighter of each channel
+    Lighten,
+    /// Absolute difference of each channel
+    Difference,
+}
+
+impl BlendMode {
+    /// Combine a single channel, both values are 0.0..=1.0
+    #[inline]
+    fn channel(&self, dst: f32, src: f32) -> f32 {
+        match self {
+            BlendMode::Normal => src,
+            BlendMode::Multiply => dst * src,
+            BlendMode::Additive => (dst + src).min(1.0),
+            BlendMode::Screen => dst + src - dst * src,
+            BlendMode::Overlay => {
+                if dst <= 0.5 {
+                    2.0 * dst * src
+                } else {
+                    1.0 - 2.0 * (1.0 - dst) * (1.0 - src)
+                }
+            }
+            BlendMode::Darken => dst.min(src),
+            BlendMode::Lighten => dst.max(src),
+            BlendMode::Difference => (dst - src).abs(),
+        }
+    }
+
+    /// Returns the color produced by drawing `src` on top of `dst` with this mode
+    pub fn blend(&self, dst: Color, src: Color) -> Color {
+        if *self == BlendMode::Normal {
+            return match src.a {
+                255 => src,
+                0 => dst,
+                _ => dst.blend(src),
+            };
+        }
+        if src.a == 0 {
+            return dst;
+        }
+        porter_duff(dst, src, *self, 1.0, 1.0 - src.a as f32 / 255.0)
+    }
+}
+
+/// Porter-Duff operator used to composite colors being drawn with the existing pixels
+///
+/// Unlike [BlendMode] (which controls how the color channels are mixed) this controls which
+/// parts of the source and existing pixels are kept, this allows pixels to be erased or masked
+///
+/// Set with [Graphics::set_composite_mode](crate::Graphics::set_composite_mode)
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum CompositeMode {
+    /// Replace the existing pixel, including alpha, a color with alpha 0 will make the pixel transparent
+    Source,
+    /// Draw on top of the existing pixel (standard drawing)
+    #[default]
+    SourceOver,
+    /// Draw only where the existing pixel is opaque, the result is transparent elsewhere
+    SourceIn,
+    /// Erase the existing pixel by the alpha of the color being drawn, the color itself is ignored
+    DestinationOut,
+    /// Make the pixel transparent
+    Clear,
+    /// Keep the source where the existing pixel is transparent and the existing pixel where
+    /// the source is transparent
+    Xor,
+}
+
+impl CompositeMode {
+    /// Returns the color produced by drawing `src` on top of `dst` with this operator,
+    /// with the color channels mixed using `blend_mode`
+    pub fn composite(&self, dst: Color, src: Color, blend_mode: BlendMode) -> Color {
+        let dst_a = dst.a as f32 / 255.0;
+        let src_a = src.a as f32 / 255.0;
+        match self {
+            CompositeMode::SourceOver => blend_mode.blend(dst, src),
+            CompositeMode::Clear => TRANSPARENT,
+            CompositeMode::Source if blend_mode == BlendMode::Normal => src,
+            CompositeMode::Source => porter_duff(dst, src, blend_mode, 1.0, 0.0),
+            CompositeMode::SourceIn => porter_duff(dst, src, blend_mode, dst_a, 0.0),
+            CompositeMode::DestinationOut => porter_duff(dst, src, blend_mode, 0.0, 1.0 - src_a),
+            CompositeMode::Xor => porter_duff(dst, src, blend_mode, 1.0 - dst_a, 1.0 - src_a),
+        }
+    }
+}
+
+/// Composite `src` onto `dst` where `src_factor` and `dst_factor` are the Porter-Duff
+/// coverage factors and `blend_mode` mixes the color channels where both are present
+fn porter_duff(
+    dst: Color,
+    src: Color,
+    blend_mode: BlendMode,
+    src_factor: f32,
+    dst_factor: f32,
+) -> Color {
+    let dst_a = dst.a as f32 / 255.0;
+    let src_a = src.a as f32 / 255.0;
+    let src_weight = src_a * src_factor;
+    let dst_weight = dst_a * dst_factor;
+    let out_a = src_weight + dst_weight;
+    if out_a <= 0.0 {
+        return TRANSPARENT;
+    }
+    let mix = |d: u8, s: u8| -> u8 {
+        let d = d as f32 / 255.0;
+        let s = s as f32 / 255.0;
+        let blended = (1.0 - dst_a) * s + dst_a * blend_mode.channel(d, s);
+        let value = (src_weight * blended + dst_weight * d) / out_a;
+        (value * 255.0).round().clamp(0.0, 255.0) as u8
+    };
+    Color::new(
+        mix(dst.r, src.r),
+        mix(dst.g, src.g),
+        mix(dst.b, src.b),
+        (out_a * 255.0).round().clamp(0.0, 255.0) as u8,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn normal_matches_existing_behaviour() {
+        assert_eq!(BlendMode::Normal.blend(RED, BLUE), BLUE);
+        assert_eq!(BlendMode::Normal.blend(RED, BLUE.with_alpha(0)), RED);
+        assert_eq!(
+            BlendMode::Normal.blend(RED, BLUE.with_alpha(128)),
+            RED.blend(BLUE.with_alpha(128))
+        );
+    }
+
+    #[test]
+    fn opaque_modes() {
+        let dst = Color::new(200, 100, 50, 255);
+        let src = Color::new(100, 200, 50, 255);
+        assert_eq!(
+            BlendMode::Multiply.blend(dst, src),
+            Color::new(78, 78, 10, 255)
+        );
+        assert_eq!(
+            BlendMode::Additive.blend(dst, src),
+            Color::new(255, 255, 100, 255)
+        );
+        assert_eq!(
+            BlendMode::Screen.blend(dst, src),
+            Color::new(222, 222, 90, 255)
+        );
+        assert_eq!(
+            BlendMode::Overlay.blend(dst, src),
+            Color::new(188, 157, 20, 255)
+        );
+        assert_eq!(
+            BlendMode::Darken.blend(dst, src),
+            Color::new(100, 100, 50, 255)
+        );
+        assert_eq!(
+            BlendMode::Lighten.blend(dst, src),
+            Color::new(200, 200, 50, 255)
+        );
+        assert_eq!(
+            BlendMode::Difference.blend(dst, src),
+            Color::new(100, 100, 0, 255)
+        );
+    }
+
+    #[test]
+    fn partial_alpha_mixes_with_existing() {
+        let dst = Color::new(200, 200, 200, 255);
+        let src = Color::new(0, 0, 0, 128);
+        let result = BlendMode::Multiply.blend(dst, src);
+        assert_eq!(result, Color::new(100, 100, 100, 255));
+        assert_eq!(BlendMode::Multiply.blend(dst, src.with_alpha(0)), dst);
+    }
+
+    #[test]
+    fn transparent_destination_uses_source() {
+        let dst = Color::new(0, 0, 0, 0);
+        let src = Color::new(100, 150, 200, 255);
+        assert_eq!(BlendMode::Multiply.blend(dst, src), src);
+        assert_eq!(BlendMode::Difference.blend(dst, src), src);
+    }
+
+    #[test]
+    fn composite_modes() {
+        let dst = Color::new(200, 100, 50, 255);
+        let src = Color::new(100, 200, 50, 128);
+        assert_eq!(
+            CompositeMode::SourceOver.composite(dst, src, BlendMode::Normal),
+            dst.blend(src)
+        );
+        assert_eq!(
+            CompositeMode::Source.composite(dst, src, BlendMode::Normal),
+            src
+        );
+        assert_eq!(
+            CompositeMode::Source.composite(dst, TRANSPARENT, BlendMode::Normal),
+            TRANSPARENT
+        );
+        assert_eq!(
+            CompositeMode::Clear.composite(dst, src, BlendMode::Normal),
+            TRANSPARENT
+        );
+        assert_eq!(
+            CompositeMode::DestinationOut.composite(dst, RED, BlendMode::Normal),
+            TRANSPARENT
+        );
+        assert_eq!(
+            CompositeMode::DestinationOut.composite(dst, src, BlendMode::Normal),
+            dst.with_alpha(127)
+        );
+        assert_eq!(
+            CompositeMode::SourceIn.composite(TRANSPARENT, src, BlendMode::Normal),
+            TRANSPARENT
+        );
+        assert_eq!(
+            CompositeMode::SourceIn.composite(dst, src, BlendMode::Normal),
+            src
+        );
+        assert_eq!(
+            CompositeMode::Xor.composite(dst, RED, BlendMode::Normal),
+            TRANSPARENT
+        );
+        assert_eq!(
+            CompositeMode::Xor.composite(TRANSPARENT, RED, BlendMode::Normal),
+            RED
+        );
+    }
+}
@@ -0,0 +1,351 @@
+use crate::blending::CompositeMode;
+use crate::dirty::DirtyTiles;
+use crate::image::Image;
+use crate::state::GraphicsState;
+use crate::Graphics;
+use ici_files::prelude::*;
+use std::mem::{align_of, replace, size_of, swap, take, ManuallyDrop};
+use std::ops::{Deref, DerefMut};
+
+/// Pixels owned by a [Canvas]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CanvasBuffer {
+    RgbaU8(Vec<u8>),
+    RgbaU32(Vec<u32>),
+    ArgbU32(Vec<u32>),
+}
+
+impl CanvasBuffer {
+    fn to_pixels(&self) -> Vec<Color> {
+        match self {
+            CanvasBuffer::RgbaU8(buf) => buf
+                .chunks_exact(4)
+                .map(|p| Color::new(p[0], p[1], p[2], p[3]))
+                .collect(),
+            CanvasBuffer::RgbaU32(buf) => buf.iter().copied().map(Color::from_rgba).collect(),
+            CanvasBuffer::ArgbU32(buf) => buf.iter().copied().map(Color::from_argb).collect(),
+        }
+    }
+
+    /// Convert to colors, the `Vec<u8>` allocation is reused as a `Vec<Color>`
+    fn into_pixels(self) -> Vec<Color> {
+        const _: () = assert!(size_of::<Color>() == 4 && align_of::<Color>() == 1);
+
+        match self {
+            CanvasBuffer::RgbaU8(buf) if buf.len() % 4 == 0 && buf.capacity() % 4 == 0 => {
+                let mut buf = ManuallyDrop::new(buf);
+                let len = buf.len() / 4;
+                let cap = buf.capacity() / 4;
+                let ptr = buf.as_mut_ptr();
+                // SAFETY: Color is 4 bytes with an alignment of 1 (checked above) so the
+                // allocation has the same layout as `cap` colors, each pixel is read before
+                // the color is written over the same 4 bytes
+                unsafe {
+                    for i in 0..len {
+                        let p = ptr.add(i * 4);
+                        let color = Color::new(*p, *p.add(1), *p.add(2), *p.add(3));
+                        ptr.cast::<Color>().add(i).write(color);
+                    }
+                    Vec::from_raw_parts(ptr.cast::<Color>(), len, cap)
+                }
+            }
+            CanvasBuffer::RgbaU8(buf) => CanvasBuffer::RgbaU8(buf).to_pixels(),
+            CanvasBuffer::RgbaU32(buf) => buf.into_iter().map(Color::from_rgba).collect(),
+            CanvasBuffer::ArgbU32(buf) => buf.into_iter().map(Color::from_argb).collect(),
+        }
+    }
+}
+
+/// A buffer and [Graphics] settings that can be stored without a lifetime
+///
+/// Use [graphics](Canvas::graphics) to draw, any changes to the translate, transform, clip, etc
+/// are kept for next time
+///
+/// # Usage
+/// ```
+///# use buffer_graphics_lib::prelude::*;
+/// struct Scene {
+///     canvas: Canvas,
+/// }
+///
+/// let mut scene = Scene { canvas: Canvas::new_u8_rgba(40, 30) };
+/// scene.canvas.graphics().set_translate(coord!(10, 10));
+/// scene.canvas.graphics().draw_rect(Rect::new((0, 0), (4, 4)), fill(RED));
+/// let image = scene.canvas.into_image();
+/// assert_eq!(image.get_pixel(10, 10), RED);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    buffer: CanvasBuffer,
+    width: usize,
+    height: usize,
+    state: GraphicsState,
+    state_stack: Vec<GraphicsState>,
+    dirty: Option<DirtyTiles>,
+}
+
+impl Canvas {
+    fn new(buffer: CanvasBuffer, width: usize, height: usize) -> Self {
+        Self {
+            buffer,
+            width,
+            height,
+            state: GraphicsState::new(width, height),
+            state_stack: vec![],
+            dirty: None,
+        }
+    }
+
+    /// Create a transparent canvas backed by a `Vec<u8>` (RGBA)
+    pub fn new_u8_rgba(width: usize, height: usize) -> Self {
+        Canvas::new(
+            CanvasBuffer::RgbaU8(Graphics::create_buffer_u8(width, height)),
+            width,
+            height,
+        )
+    }
+
+    /// Create a transparent canvas backed by a `Vec<u32>` (RGBA)
+    pub fn new_u32_rgba(width: usize, height: usize) -> Self {
+        Canvas::new(
+            CanvasBuffer::RgbaU32(Graphics::create_buffer_u32(width, height)),
+            width,
+            height,
+        )
+    }
+
+    /// Create a transparent canvas backed by a `Vec<u32>` (ARGB)
+    pub fn new_u32_argb(width: usize, height: usize) -> Self {
+        Canvas::new(
+            CanvasBuffer::ArgbU32(Graphics::create_buffer_u32(width, height)),
+            width,
+            height,
+        )
+    }
+
+    /// Create a canvas (backed by a `Vec<u8>`) containing `image`
+    pub fn from_image(image: &Image) -> Self {
+        let mut canvas = Canvas::new_u8_rgba(image.width(), image.height());
+        canvas
+            .graphics()
+            .with_composite_mode(CompositeMode::Source, |g| g.draw_image((0, 0), image));
+        canvas
+    }
+}
+
+impl Canvas {
+    #[inline(always)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[inline(always)]
+    pub fn buffer(&self) -> &CanvasBuffer {
+        &self.buffer
+    }
+
+    #[inline]
+    pub fn into_buffer(self) -> CanvasBuffer {
+        self.buffer
+    }
+
+    /// Borrow as a [Graphics] to draw to this canvas
+    ///
+    /// This doesn't allocate or copy any pixels, the settings are moved into the [Graphics]
+    /// and back to the canvas when it's dropped
+    pub fn graphics(&mut self) -> CanvasGraphics<'_> {
+        let graphics = match &mut self.buffer {
+            CanvasBuffer::RgbaU8(buf) => Graphics::new_u8_rgba(buf, self.width, self.height),
+            CanvasBuffer::RgbaU32(buf) => Graphics::new_u32_rgba(buf, self.width, self.height),
+            CanvasBuffer::ArgbU32(buf) => Graphics::new_u32_argb(buf, self.width, self.height),
+        };
+        let mut graphics = graphics.expect(
+            "Canvas buffer is wrong size, please create GitHub issue for buffer-graphics-lib",
+        );
+        let state = replace(&mut self.state, GraphicsState::new(self.width, self.height));
+        graphics.swap_state(state);
+        swap(&mut graphics.state_stack, &mut self.state_stack);
+        graphics.dirty = self.dirty.take();
+        CanvasGraphics {
+            graphics,
+            state: &mut self.state,
+            state_stack: &mut self.state_stack,
+            dirty: &mut self.dirty,
+        }
+    }
+
+    /// Change the size of the canvas, pixels inside both the old and new size are kept and
+    /// new pixels are transparent
+    ///
+    /// The clip is reset (including in any saved states) as it's the wrong size, if dirty regions
+    /// are being tracked the whole canvas is marked as dirty
+    pub fn resize(&mut self, width: usize, height: usize) {
+        fn copy_rows<T: Copy + Default>(
+            buf: &mut Vec<T>,
+            pixel_size: usize,
+            (old_width, old_height): (usize, usize),
+            (width, height): (usize, usize),
+        ) {
+            let mut resized = vec![T::default(); width * height * pixel_size];
+            let row_len = old_width.min(width) * pixel_size;
+            for y in 0..old_height.min(height) {
+                let src = y * old_width * pixel_size;
+                let dest = y * width * pixel_size;
+                resized[dest..dest + row_len].copy_from_slice(&buf[src..src + row_len]);
+            }
+            *buf = resized;
+        }
+
+        let old_size = (self.width, self.height);
+        match &mut self.buffer {
+            CanvasBuffer::RgbaU8(buf) => copy_rows(buf, 4, old_size, (width, height)),
+            CanvasBuffer::RgbaU32(buf) => copy_rows(buf, 1, old_size, (width, height)),
+            CanvasBuffer::ArgbU32(buf) => copy_rows(buf, 1, old_size, (width, height)),
+        }
+        self.width = width;
+        self.height = height;
+        for state in self.state_stack.iter_mut().chain([&mut self.state]) {
+            state.reset_clip(width, height);
+        }
+        if let Some(dirty) = &mut self.dirty {
+            *dirty = DirtyTiles::new(width, height, dirty.tile_size());
+            dirty.mark_region(0, 0, width, height);
+        }
+    }
+
+    /// Copy the pixels to an image
+    pub fn to_image(&self) -> Image {
+        Image::new(self.buffer.to_pixels(), self.width, self.height)
+            .expect("Canvas to image failed, please create GitHub issue for buffer-graphics-lib")
+    }
+
+    /// Convert to an image
+    ///
+    /// For [CanvasBuffer::RgbaU8] the buffer is moved into the image without copying
+    pub fn into_image(self) -> Image {
+        Image::new(self.buffer.into_pixels(), self.width, self.height)
+            .expect("Canvas to image failed, please create GitHub issue for buffer-graphics-lib")
+    }
+}
+
+/// [Graphics] borrowed from a [Canvas], see [Canvas::graphics]
+///
+/// Derefs to [Graphics], the settings are saved back to the canvas when this is dropped
+pub struct CanvasGraphics<'a> {
+    graphics: Graphics<'a>,
+    state: &'a mut GraphicsState,
+    state_stack: &'a mut Vec<GraphicsState>,
+    dirty: &'a mut Option<DirtyTiles>,
+}
+
+impl<'a> Deref for CanvasGraphics<'a> {
+    type Target = Graphics<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.graphics
+    }
+}
+
+impl DerefMut for CanvasGraphics<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.graphics
+    }
+}
+
+impl Drop for CanvasGraphics<'_> {
+    fn drop(&mut self) {
+        let width = self.graphics.width;
+        let height = self.graphics.height;
+        *self.state = self.graphics.swap_state(GraphicsState::new(width, height));
+        *self.state_stack = take(&mut self.graphics.state_stack);
+        *self.dirty = self.graphics.dirty.take();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::*;
+
+    #[test]
+    fn settings_are_kept() {
+        let mut canvas = Canvas::new_u32_argb(6, 6);
+        {
+            let mut graphics = canvas.graphics();
+            graphics.set_translate(coord!(1, 1));
+            graphics
+                .clip_mut()
+                .set_valid_rect(Rect::new((0, 0), (3, 3)));
+            graphics.save();
+            graphics.set_blend_mode(BlendMode::Multiply);
+        }
+        canvas.graphics().clear_aware(RED);
+        assert_eq!(canvas.graphics().blend_mode(), BlendMode::Multiply);
+        canvas.graphics().restore();
+        assert_eq!(canvas.graphics().blend_mode(), BlendMode::Normal);
+        assert_eq!(canvas.graphics().saved_state_count(), 0);
+
+        let image = canvas.to_image();
+        assert_eq!(image.get_pixel(0, 0), TRANSPARENT);
+        assert_eq!(image.get_pixel(1, 1), RED);
+        assert_eq!(image.get_pixel(3, 3), RED);
+        assert_eq!(image.get_pixel(4, 4), TRANSPARENT);
+        assert_eq!(Canvas::from_image(&image).into_image(), image);
+    }
+
+    #[test]
+    fn resize_keeps_pixels() {
+        for mut canvas in [Canvas::new_u8_rgba(4, 3), Canvas::new_u32_rgba(4, 3)] {
+            canvas
+                .graphics()
+                .clip_mut()
+                .set_valid_rect(Rect::new((0, 0), (1, 1)));
+            canvas.graphics().clear_aware(BLUE);
+            canvas.graphics().set_pixel(3, 2, GREEN);
+            canvas.resize(5, 2);
+            assert_eq!(canvas.width(), 5);
+            assert!(canvas.graphics().clip().is_nothing());
+            canvas.graphics().set_pixel(4, 1, RED);
+            let image = canvas.to_image();
+            assert_eq!(image.width(), 5);
+            assert_eq!(image.height(), 2);
+            assert_eq!(image.get_pixel(1, 1), BLUE);
+            assert_eq!(image.get_pixel(2, 1), TRANSPARENT);
+            assert_eq!(image.get_pixel(4, 1), RED);
+        }
+    }
+
+    #[test]
+    fn dirty_regions_are_kept() {
+        let mut canvas = Canvas::new_u8_rgba(8, 8);
+        canvas.graphics().track_dirty_regions(4);
+        canvas.graphics().set_pixel(1, 5, RED);
+        assert_eq!(
+            canvas.graphics().take_dirty_regions(),
+            vec![Rect::new((0, 4), (3, 7))]
+        );
+        canvas.resize(6, 3);
+        assert_eq!(
+            canvas.graphics().take_dirty_regions(),
+            vec![Rect::new((0, 0), (5, 2))]
+        );
+    }
+
+    #[test]
+    fn into_image_matches_to_image() {
+        for mut canvas in [
+            Canvas::new_u8_rgba(3, 2),
+            Canvas::new_u32_rgba(3, 2),
+            Canvas::new_u32_argb(3, 2),
+        ] {
+            canvas.graphics().clear(BLUE);
+            canvas.graphics().set_pixel(2, 1, RED.with_alpha(100));
+            let expected = canvas.to_image();
+            assert_eq!(canvas.into_image(), expected);
+        }
+    }
+}
@@ -38,6 +38,17 @@ enum ClipMode {
     Custom(Vec<bool>),
 }
 
+/// Part of a row that is valid, see [Clip::row_span]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum RowClip {
+    /// No pixels are valid
+    Empty,
+    /// Only pixels from the first to second value (inclusive) are valid
+    Span(isize, isize),
+    /// Each pixel from the first to second value (inclusive) has to be checked with [Clip::is_valid]
+    PerPixel(isize, isize),
+}
+
 /// Clip has four modes:
 /// * Nothing - All pixels are valid
 /// * Simple - Only pixels in the shape (rect or circle) are valid
@@ -113,6 +124,31 @@ impl Clip {
             Custom(map) => map[u],
         }
     }
+
+    /// Returns which pixels of row `y` from `start` to `end` (inclusive) are valid, this is
+    /// only calculated for rect clips
+    pub(crate) fn row_span(&self, y: isize, start: isize, end: isize) -> RowClip {
+        match &self.mode {
+            Nothing => RowClip::Span(start, end),
+            Simple(ClipShape::Box(rect)) => {
+                if y < rect.top() || y > rect.bottom() {
+                    RowClip::Empty
+                } else {
+                    RowClip::Span(start.max(rect.left()), end.min(rect.right()))
+                }
+            }
+            _ => RowClip::PerPixel(start, end),
+        }
+    }
+
+    /// Rect containing every valid pixel, None if it's not known (any pixel could be valid)
+    pub(crate) fn bounds(&self) -> Option<Rect> {
+        match &self.mode {
+            Simple(ClipShape::Box(rect)) => Some(rect.clone()),
+            Simple(ClipShape::Round(circle)) => Some(circle.as_outer_rect()),
+            _ => None,
+        }
+    }
 }
 
 impl Clip {
@@ -194,6 +230,58 @@ impl Clip {
     }
 }
 
+impl Clip {
+    /// Returns a clip where only pixels valid in both `self` and `other` are valid
+    ///
+    /// If both clips are rects the result is also a rect, otherwise it's a custom pixel map
+    pub fn intersect(&self, other: &Clip) -> Clip {
+        let mode = match (&self.mode, &other.mode) {
+            (Nothing, _) if self.width == other.width && self.height == other.height => {
+                return other.clone()
+            }
+            (_, Nothing) => return self.clone(),
+            (Simple(ClipShape::Box(lhs)), Simple(ClipShape::Box(rhs))) => {
+                let left = lhs.left().max(rhs.left());
+                let top = lhs.top().max(rhs.top());
+                let right = lhs.right().min(rhs.right());
+                let bottom = lhs.bottom().min(rhs.bottom());
+                if left <= right && top <= bottom {
+                    Simple(ClipShape::Box(Rect::new((left, top), (right, bottom))))
+                } else {
+                    Custom(vec![false; self.width * self.height])
+                }
+            }
+            (_, _) => {
+                let mut map = self.current_pixel_map();
+                for y in 0..self.height {
+                    for x in 0..self.width {
+                        let i = x + y * self.width;
+                        map[i] = map[i]
+                            && x < other.width
+                            && y < other.height
+                            && other.is_valid((x as isize, y as isize));
+                    }
+                }
+                Custom(map)
+            }
+        };
+        Clip {
+            width: self.width,
+            height: self.height,
+            mode,
+            valid_pixel_map: None,
+            auto_build_map: self.auto_build_map,
+        }
+    }
+
+    fn current_pixel_map(&self) -> Vec<bool> {
+        match (&self.mode, &self.valid_pixel_map) {
+            (Complex(_), Some(map)) => map.clone(),
+            _ => self.build_pixel_map(),
+        }
+    }
+}
+
 impl Clip {
     pub fn update_pixel_map(&mut self) {
         if self.is_complex() {
@@ -311,4 +399,35 @@ mod test {
         ];
         assert_eq!(clip.get_pixel_map(), expected);
     }
+
+    #[test]
+    fn intersect_rects() {
+        let mut lhs = Clip::new(4, 4);
+        lhs.set_valid_rect(Rect::new((0, 0), (2, 2)));
+        let mut rhs = Clip::new(4, 4);
+        rhs.set_valid_rect(Rect::new((1, 1), (3, 3)));
+        let clip = lhs.intersect(&rhs);
+        assert!(clip.is_simple());
+        let mut expected = Clip::new(4, 4);
+        expected.set_valid_rect(Rect::new((1, 1), (2, 2)));
+        assert_eq!(clip, expected);
+
+        assert_eq!(Clip::new(4, 4).intersect(&rhs), rhs);
+        assert_eq!(rhs.intersect(&Clip::new(4, 4)), rhs);
+    }
+
+    #[test]
+    fn intersect_mixed() {
+        let mut lhs = Clip::new(4, 4);
+        lhs.set_valid_rect(Rect::new((0, 0), (1, 3)));
+        let mut rhs = Clip::new(4, 4);
+        rhs.remove_rect(Rect::new((0, 2), (3, 3)));
+        let mut clip = lhs.intersect(&rhs);
+        assert!(clip.is_custom());
+        let expected = vec![
+            true, true, false, false, true, true, false, false, false, false, false, false, false,
+            false, false, false,
+        ];
+        assert_eq!(clip.get_pixel_map(), expected);
+    }
 }
@@ -0,0 +1,237 @@
+use crate::dirty::DirtyTiles;
+use crate::image::Image;
+use crate::{Graphics, GraphicsError};
+use graphics_shapes::coord::Coord;
+use graphics_shapes::rect::Rect;
+use ici_files::prelude::*;
+
+/// Which pixels are different between two frames, see [Image::diff] and [Graphics::diff]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FrameDiff {
+    mask: Vec<bool>,
+    width: usize,
+    height: usize,
+}
+
+impl FrameDiff {
+    fn new(lhs: &[Color], rhs: &[Color], width: usize, height: usize) -> Self {
+        Self {
+            mask: lhs.iter().zip(rhs).map(|(lhs, rhs)| lhs != rhs).collect(),
+            width,
+            height,
+        }
+    }
+
+    #[inline(always)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// One entry per pixel (row by row), true if the pixel is different
+    #[inline(always)]
+    pub fn mask(&self) -> &[bool] {
+        &self.mask
+    }
+
+    #[inline]
+    pub fn is_changed(&self, x: usize, y: usize) -> bool {
+        self.mask[x + y * self.width]
+    }
+
+    /// Returns true if the frames are the same
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        !self.mask.contains(&true)
+    }
+
+    /// Number of pixels that are different
+    pub fn changed_count(&self) -> usize {
+        self.mask.iter().filter(|changed| **changed).count()
+    }
+
+    /// Rects covering the pixels that are different, no two rects touch or overlap
+    ///
+    /// Runs of changed pixels on each row are merged with the same runs on the following rows,
+    /// then any rects that touch (including at a corner) or overlap are replaced with the single
+    /// rect covering both, so a rect may include some unchanged pixels
+    pub fn changed_regions(&self) -> Vec<Rect> {
+        let mut merged: Vec<Rect> = vec![];
+        for mut rect in self.changed_tiles(1) {
+            while let Some(i) = merged.iter().position(|other| touches(other, &rect)) {
+                let other = merged.remove(i);
+                let (top_left, bottom_right) = (rect.top_left(), rect.bottom_right());
+                rect = Rect::new(
+                    (
+                        top_left.x.min(other.top_left().x),
+                        top_left.y.min(other.top_left().y),
+                    ),
+                    (
+                        bottom_right.x.max(other.bottom_right().x),
+                        bottom_right.y.max(other.bottom_right().y),
+                    ),
+                );
+            }
+            merged.push(rect);
+        }
+        merged
+    }
+
+    /// Rects covering every `tile_size`x`tile_size` tile that contains a changed pixel, this
+    /// produces fewer (but larger) rects than [changed_regions](FrameDiff::changed_regions)
+    pub fn changed_tiles(&self, tile_size: usize) -> Vec<Rect> {
+        let mut tiles = DirtyTiles::new(self.width, self.height, tile_size);
+        for (i, _) in self
+            .mask
+            .iter()
+            .enumerate()
+            .filter(|(_, changed)| **changed)
+        {
+            tiles.mark(i % self.width, i / self.width);
+        }
+        tiles.take(Coord::default())
+    }
+
+    /// Create an image where changed pixels are `color` and the rest are transparent
+    pub fn to_image(&self, color: Color) -> Image {
+        let pixels = self
+            .mask
+            .iter()
+            .map(|changed| if *changed { color } else { TRANSPARENT })
+            .collect();
+        Image::new(pixels, self.width, self.height)
+            .expect("Diff to image failed, please create GitHub issue for buffer-graphics-lib")
+    }
+}
+
+/// Returns true if the rects overlap or are next to each other (including diagonally)
+fn touches(lhs: &Rect, rhs: &Rect) -> bool {
+    lhs.top_left().x <= rhs.bottom_right().x + 1
+        && rhs.top_left().x <= lhs.bottom_right().x + 1
+        && lhs.top_left().y <= rhs.bottom_right().y + 1
+        && rhs.top_left().y <= lhs.bottom_right().y + 1
+}
+
+impl Image {
+    /// Compare every pixel with `other`
+    ///
+    /// # Usage
+    /// ```
+    ///# use buffer_graphics_lib::prelude::*;
+    /// let previous = Image::new_blank(4, 4);
+    /// let mut next = previous.clone();
+    /// next.set_pixel(1, 2, RED);
+    /// let diff = previous.diff(&next).unwrap();
+    /// assert_eq!(diff.changed_regions(), vec![Rect::new((1, 2), (1, 2))]);
+    /// ```
+    ///
+    /// # Errors
+    /// * `GraphicsError::DiffSize` if the images are different sizes
+    pub fn diff(&self, other: &Image) -> Result<FrameDiff, GraphicsError> {
+        if self.width() != other.width() || self.height() != other.height() {
+            return Err(GraphicsError::DiffSize(
+                self.width(),
+                self.height(),
+                other.width(),
+                other.height(),
+            ));
+        }
+        Ok(FrameDiff::new(
+            self.pixels(),
+            other.pixels(),
+            self.width(),
+            self.height(),
+        ))
+    }
+}
+
+impl Graphics<'_> {
+    /// Compare every pixel in the buffer with `image`, this ignores translate and clip
+    ///
+    /// Pixels are compared after being read from the buffer, so for formats without alpha
+    /// (such as RGB888) a transparent pixel in `image` is always different
+    ///
+    /// # Errors
+    /// * `GraphicsError::DiffSize` if the image isn't the same size as the buffer
+    pub fn diff(&self, image: &Image) -> Result<FrameDiff, GraphicsError> {
+        if self.width != image.width() || self.height != image.height() {
+            return Err(GraphicsError::DiffSize(
+                self.width,
+                self.height,
+                image.width(),
+                image.height(),
+            ));
+        }
+        Ok(FrameDiff::new(
+            &self.pixels(),
+            image.pixels(),
+            self.width,
+            self.height,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::*;
+
+    #[test]
+    fn graphics_diff() {
+        let previous = crate::make_image(6, 5, |g| g.clear(BLUE)).unwrap();
+        let mut buffer = Graphics::create_buffer_u32(6, 5);
+        let mut graphics = Graphics::new_u32_argb(&mut buffer, 6, 5).unwrap();
+        graphics.clear(BLUE);
+        assert!(graphics.diff(&previous).unwrap().is_empty());
+
+        graphics.draw_rect(Rect::new((1, 1), (2, 3)), fill(RED));
+        graphics.set_pixel(5, 1, RED);
+        let diff = graphics.diff(&previous).unwrap();
+        assert_eq!(diff.changed_count(), 7);
+        assert!(diff.is_changed(2, 3));
+        assert!(!diff.is_changed(3, 3));
+        assert_eq!(
+            diff.changed_regions(),
+            vec![Rect::new((5, 1), (5, 1)), Rect::new((1, 1), (2, 3))]
+        );
+        assert_eq!(
+            diff.changed_tiles(3),
+            vec![Rect::new((0, 0), (5, 2)), Rect::new((0, 3), (2, 4))]
+        );
+        assert_eq!(diff.to_image(RED).get_pixel(5, 1), RED);
+        assert_eq!(diff.to_image(RED).get_pixel(0, 0), TRANSPARENT);
+
+        assert!(matches!(
+            graphics.diff(&Image::new_blank(5, 6)),
+            Err(GraphicsError::DiffSize(6, 5, 5, 6))
+        ));
+        assert!(matches!(
+            previous.diff(&Image::new_blank(5, 6)),
+            Err(GraphicsError::DiffSize(6, 5, 5, 6))
+        ));
+    }
+
+    #[test]
+    fn adjacent_regions_are_merged() {
+        let previous = Image::new_blank(8, 6);
+        let mut next = previous.clone();
+        for (x, y) in [(1, 1), (2, 1), (3, 2), (3, 3), (6, 4)] {
+            next.set_pixel(x, y, RED);
+        }
+        let diff = previous.diff(&next).unwrap();
+        assert_eq!(
+            diff.changed_regions(),
+            vec![Rect::new((1, 1), (3, 3)), Rect::new((6, 4), (6, 4))]
+        );
+
+        let mut next = previous.clone();
+        for (x, y) in [(1, 1), (2, 1), (1, 2), (2, 2), (3, 2), (4, 2)] {
+            next.set_pixel(x, y, RED);
+        }
+        let diff = previous.diff(&next).unwrap();
+        assert_eq!(diff.changed_regions(), vec![Rect::new((1, 1), (4, 2))]);
+    }
+}
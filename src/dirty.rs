@@ -0,0 +1,236 @@
+use crate::Graphics;
+use graphics_shapes::coord::Coord;
+use graphics_shapes::rect::Rect;
+
+/// Grid of tiles that have been drawn to, see [Graphics::track_dirty_regions]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct DirtyTiles {
+    tile_size: usize,
+    width: usize,
+    height: usize,
+    columns: usize,
+    tiles: Vec<bool>,
+}
+
+impl DirtyTiles {
+    pub(crate) fn new(width: usize, height: usize, tile_size: usize) -> Self {
+        let tile_size = tile_size.max(1);
+        let columns = width.div_ceil(tile_size);
+        let rows = height.div_ceil(tile_size);
+        Self {
+            tile_size,
+            width,
+            height,
+            columns,
+            tiles: vec![false; columns * rows],
+        }
+    }
+
+    #[inline]
+    pub(crate) fn tile_size(&self) -> usize {
+        self.tile_size
+    }
+
+    #[inline]
+    pub(crate) fn mark(&mut self, x: usize, y: usize) {
+        let i = x / self.tile_size + (y / self.tile_size) * self.columns;
+        self.tiles[i] = true;
+    }
+
+    /// Mark pixels `start_x..=end_x` on row `y`
+    #[inline]
+    pub(crate) fn mark_span(&mut self, y: usize, start_x: usize, end_x: usize) {
+        let row = (y / self.tile_size) * self.columns;
+        self.tiles[row + start_x / self.tile_size..=row + end_x / self.tile_size].fill(true);
+    }
+
+    /// Mark pixels in the `width`x`height` region starting at `x`, `y`
+    pub(crate) fn mark_region(&mut self, x: usize, y: usize, width: usize, height: usize) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        for row in (y / self.tile_size)..=((y + height - 1) / self.tile_size) {
+            let row = row * self.columns;
+            self.tiles[row + x / self.tile_size..=row + (x + width - 1) / self.tile_size]
+                .fill(true);
+        }
+    }
+
+    /// Returns the marked tiles merged into rects (offset by `origin`) and clears all tiles
+    ///
+    /// Runs of tiles on a row are merged horizontally and then rows with the same runs are merged
+    pub(crate) fn take(&mut self, origin: Coord) -> Vec<Rect> {
+        let mut output = vec![];
+        // (first column, last column, first row) of rects that may continue on the next row
+        let mut open: Vec<(usize, usize, usize)> = vec![];
+        let rows = self.tiles.len() / self.columns.max(1);
+        for row in 0..=rows {
+            let mut runs = vec![];
+            if row < rows {
+                let tiles = &self.tiles[row * self.columns..(row + 1) * self.columns];
+                let mut column = 0;
+                while column < tiles.len() {
+                    if tiles[column] {
+                        let start = column;
+                        while column + 1 < tiles.len() && tiles[column + 1] {
+                            column += 1;
+                        }
+                        runs.push((start, column));
+                    }
+                    column += 1;
+                }
+            }
+            let mut still_open = vec![];
+            for (start, end, top) in open.drain(..) {
+                if let Some(i) = runs.iter().position(|run| *run == (start, end)) {
+                    runs.remove(i);
+                    still_open.push((start, end, top));
+                } else {
+                    output.push(self.rect(start, end, top, row - 1, origin));
+                }
+            }
+            still_open.extend(runs.into_iter().map(|(start, end)| (start, end, row)));
+            open = still_open;
+        }
+        self.tiles.fill(false);
+        output
+    }
+
+    /// Pixel rect covering the tiles, clamped to the buffer
+    fn rect(&self, start: usize, end: usize, top: usize, bottom: usize, origin: Coord) -> Rect {
+        let left = start * self.tile_size;
+        let right = ((end + 1) * self.tile_size).min(self.width) - 1;
+        let top = top * self.tile_size;
+        let bottom = ((bottom + 1) * self.tile_size).min(self.height) - 1;
+        Rect::new(
+            origin + (left as isize, top as isize),
+            origin + (right as isize, bottom as isize),
+        )
+    }
+}
+
+impl Graphics<'_> {
+    /// Start recording which parts of the buffer are drawn to, use
+    /// [take_dirty_regions](Graphics::take_dirty_regions) to get them
+    ///
+    /// The buffer is split into `tile_size`x`tile_size` tiles and any tile that's drawn to is
+    /// marked as dirty, smaller tiles produce more precise regions but more of them.
+    /// Calling this again clears any recorded regions
+    ///
+    /// Creating a [viewport](Graphics::viewport) marks the whole viewport as dirty and
+    /// [split_bands](Graphics::split_bands) marks the whole buffer as dirty, as drawing to them
+    /// can't be recorded
+    pub fn track_dirty_regions(&mut self, tile_size: usize) {
+        self.dirty = Some(DirtyTiles::new(self.width, self.height, tile_size));
+    }
+
+    /// Stop recording which parts of the buffer are drawn to
+    pub fn stop_tracking_dirty_regions(&mut self) {
+        self.dirty = None;
+    }
+
+    #[inline]
+    pub fn is_tracking_dirty_regions(&self) -> bool {
+        self.dirty.is_some()
+    }
+
+    /// Returns the rects (in buffer coordinates, i.e. not translated) that have been drawn to
+    /// since tracking started or this was last called
+    ///
+    /// Returns an empty list if not tracking, see [track_dirty_regions](Graphics::track_dirty_regions)
+    ///
+    /// # Usage
+    /// ```
+    ///# use buffer_graphics_lib::prelude::*;
+    ///# fn upload(rect: Rect) {}
+    ///# fn doc(graphics: &mut Graphics) {
+    /// graphics.track_dirty_regions(8);
+    /// graphics.draw_circle(Circle::new((30, 30), 10), fill(RED));
+    /// for rect in graphics.take_dirty_regions() {
+    ///     upload(rect);
+    /// }
+    ///# }
+    /// ```
+    pub fn take_dirty_regions(&mut self) -> Vec<Rect> {
+        let origin = self.origin;
+        self.dirty
+            .as_mut()
+            .map(|dirty| dirty.take(origin))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn tiles_merge() {
+        let mut tiles = DirtyTiles::new(10, 10, 3);
+        assert!(tiles.take(Coord::default()).is_empty());
+        tiles.mark(0, 0);
+        tiles.mark_span(4, 3, 5);
+        tiles.mark_region(3, 6, 1, 4);
+        tiles.mark(9, 9);
+        assert_eq!(
+            tiles.take(coord!(0, 2)),
+            vec![
+                Rect::new((0, 2), (2, 4)),
+                Rect::new((3, 5), (5, 11)),
+                Rect::new((9, 11), (9, 11)),
+            ]
+        );
+        assert!(tiles.take(Coord::default()).is_empty());
+    }
+
+    #[test]
+    fn drawing_is_tracked() {
+        let mut buf = Graphics::create_buffer_u8(20, 20);
+        let mut graphics = Graphics::new_u8_rgba(&mut buf, 20, 20).unwrap();
+        graphics.draw_rect(Rect::new((0, 0), (19, 19)), fill(RED));
+        assert!(graphics.take_dirty_regions().is_empty());
+
+        graphics.track_dirty_regions(1);
+        graphics.set_translate(coord!(2, 1));
+        graphics.draw_rect(Rect::new((0, 0), (3, 2)), fill(RED));
+        graphics.set_pixel(10, 10, BLUE);
+        graphics.draw_image((-5, 15), &Image::new(vec![GREEN; 4], 2, 2).unwrap());
+        assert_eq!(
+            graphics.take_dirty_regions(),
+            vec![Rect::new((2, 1), (5, 3)), Rect::new((12, 11), (12, 11))]
+        );
+
+        graphics.clear(BLACK);
+        assert_eq!(
+            graphics.take_dirty_regions(),
+            vec![Rect::new((0, 0), (19, 19))]
+        );
+        let _ = graphics.viewport((4, 4), 2, 3).unwrap();
+        assert_eq!(
+            graphics.take_dirty_regions(),
+            vec![Rect::new((4, 4), (5, 6))]
+        );
+    }
+
+    #[test]
+    fn only_written_pixels_are_tracked() {
+        let mut buf = Graphics::create_buffer_u8(8, 8);
+        let mut graphics = Graphics::new_u8_rgba(&mut buf, 8, 8).unwrap();
+        graphics.track_dirty_regions(1);
+        graphics
+            .clip_mut()
+            .custom((0..64).map(|i| i % 8 <= 2).collect());
+        let mut image = Image::new(vec![TRANSPARENT; 16], 4, 4).unwrap();
+        for (x, y) in [(1, 1), (2, 1), (1, 2), (2, 2)] {
+            image.set_pixel(x, y, GREEN);
+        }
+        graphics.draw_image((1, 1), &image);
+        graphics.draw_rect(Rect::new((0, 0), (7, 7)), fill(TRANSPARENT));
+        graphics.draw_rect(Rect::new((0, 5), (7, 5)), fill(RED.with_alpha(100)));
+        assert_eq!(
+            graphics.take_dirty_regions(),
+            vec![Rect::new((2, 2), (2, 3)), Rect::new((0, 5), (2, 5))]
+        );
+    }
+}
@@ -1,4 +1,6 @@
+use crate::paint::Paint;
 use crate::shapes::CreateDrawable;
+use crate::stroke::StrokeStyle;
 use graphics_shapes::coord::Coord;
 use graphics_shapes::{IntersectsContains, Shape};
 use ici_files::prelude::*;
@@ -6,22 +8,33 @@ use ici_files::prelude::*;
 use serde::{Deserialize, Serialize};
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum DrawType {
     Stroke(Color),
     Fill(Color),
+    /// Outline drawn with a width, caps and joins, see [StrokeStyle]
+    StrokeWith(Color, StrokeStyle),
+    /// Filled with colors that vary across the shape, see [Paint]
+    FillPaint(Paint),
+    /// Filled with the first color and then outlined with the second color
+    FillAndStroke(Color, Color),
 }
 
 impl DrawType {
     pub fn is_stroke(&self) -> bool {
-        matches!(self, DrawType::Stroke(_))
+        matches!(self, DrawType::Stroke(_) | DrawType::StrokeWith(..))
     }
 
+    /// The color of the shape, for [DrawType::FillPaint] this is the color at the start of the
+    /// paint and for [DrawType::FillAndStroke] this is the fill color
     #[inline]
     pub fn color(&self) -> Color {
-        *match self {
-            DrawType::Stroke(c) => c,
-            DrawType::Fill(c) => c,
+        match self {
+            DrawType::Stroke(c) => *c,
+            DrawType::Fill(c) => *c,
+            DrawType::StrokeWith(c, _) => *c,
+            DrawType::FillPaint(paint) => paint.first_color(),
+            DrawType::FillAndStroke(c, _) => *c,
         }
     }
 }
@@ -36,9 +49,24 @@ pub fn stroke(color: Color) -> DrawType {
     DrawType::Stroke(color)
 }
 
+#[inline]
+pub fn fill_paint(paint: Paint) -> DrawType {
+    DrawType::FillPaint(paint)
+}
+
+#[inline]
+pub fn fill_and_stroke(fill: Color, stroke: Color) -> DrawType {
+    DrawType::FillAndStroke(fill, stroke)
+}
+
+#[inline]
+pub fn stroke_with(color: Color, style: StrokeStyle) -> DrawType {
+    DrawType::StrokeWith(color, style)
+}
+
 /// Represents a shape that is made of points that can be drawn either as a outline or solid
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Drawable<T: Clone> {
     obj: T,
     draw_type: DrawType,
@@ -51,8 +79,8 @@ impl<T: Clone> Drawable<T> {
         &self.obj
     }
     #[inline]
-    pub fn draw_type(&self) -> DrawType {
-        self.draw_type
+    pub fn draw_type(&self) -> &DrawType {
+        &self.draw_type
     }
     #[inline]
     pub fn drawing_points(&self) -> &Vec<Coord> {
@@ -84,37 +112,37 @@ where
 {
     pub fn with_translation<P: Into<Coord>>(&self, delta: P) -> Drawable<T> {
         let moved = self.obj.translate_by(delta.into());
-        Drawable::from_obj(moved, self.draw_type)
+        Drawable::from_obj(moved, self.draw_type.clone())
     }
 
     pub fn with_move<P: Into<Coord>>(&self, xy: P) -> Drawable<T> {
         let moved = self.obj.move_to(xy.into());
-        Drawable::from_obj(moved, self.draw_type)
+        Drawable::from_obj(moved, self.draw_type.clone())
     }
 
     pub fn with_move_center<P: Into<Coord>>(&self, xy: P) -> Drawable<T> {
         let moved = self.obj.move_center_to(xy.into());
-        Drawable::from_obj(moved, self.draw_type)
+        Drawable::from_obj(moved, self.draw_type.clone())
     }
 
     pub fn with_scale(&self, scale: f32) -> Drawable<T> {
         let moved = self.obj.scale(scale);
-        Drawable::from_obj(moved, self.draw_type)
+        Drawable::from_obj(moved, self.draw_type.clone())
     }
 
     pub fn with_scale_around<P: Into<Coord>>(&self, scale: f32, point: P) -> Drawable<T> {
         let moved = self.obj.scale_around(scale, point.into());
-        Drawable::from_obj(moved, self.draw_type)
+        Drawable::from_obj(moved, self.draw_type.clone())
     }
 
     pub fn with_rotation(&self, degrees: isize) -> Drawable<T> {
         let rotated = self.obj.rotate(degrees);
-        Drawable::from_obj(rotated, self.draw_type)
+        Drawable::from_obj(rotated, self.draw_type.clone())
     }
 
     pub fn with_rotation_around<P: Into<Coord>>(&self, degrees: isize, point: P) -> Drawable<T> {
         let rotated = self.obj.rotate_around(degrees, point.into());
-        Drawable::from_obj(rotated, self.draw_type)
+        Drawable::from_obj(rotated, self.draw_type.clone())
     }
 
     pub fn left(&self) -> isize {
@@ -1,11 +1,16 @@
-use crate::clipping::Clip;
+use crate::blending::{BlendMode, CompositeMode};
+use crate::clipping::RowClip;
 use crate::drawable::{DrawType, Drawable};
 use crate::image::Image;
+use crate::paint::PixelColor;
+use crate::pixel_format::{color_to_gray, color_to_rgb565};
 use crate::prelude::PixelFont;
+use crate::shapes::spans::for_each_span;
 use crate::shapes::CreateDrawable;
 use crate::text::format::TextFormat;
 use crate::text::pos::TextPos;
 use crate::text::{chr_to_code, Text};
+use crate::transform::Transform;
 use crate::GraphicsBuffer::RgbaU8;
 use crate::{Graphics, GraphicsBuffer, GraphicsError};
 use graphics_shapes::circle::Circle;
@@ -13,11 +18,13 @@ use graphics_shapes::coord::Coord;
 use graphics_shapes::polygon::Polygon;
 use graphics_shapes::prelude::Ellipse;
 use graphics_shapes::rect::Rect;
+use graphics_shapes::shape_box::ShapeBox;
 use graphics_shapes::triangle::Triangle;
+use graphics_shapes::Shape;
 use ici_files::palette::simplify_palette_to_fit;
 use ici_files::prelude::*;
 use std::collections::{HashMap, HashSet};
-use std::mem::swap;
+use std::mem::{swap, take};
 
 /// Represents anything that [Graphics] can render
 pub trait Renderable<T> {
@@ -25,36 +32,71 @@ pub trait Renderable<T> {
 }
 
 #[inline]
-pub(crate) fn index_u8(width: usize, x: usize, y: usize) -> usize {
-    (x + y * width) * 4
+pub(crate) fn index_u8(stride: usize, x: usize, y: usize) -> usize {
+    x * 4 + y * stride
 }
 
 #[inline]
-pub(crate) fn index_u32(width: usize, x: usize, y: usize) -> usize {
-    x + y * width
+pub(crate) fn index_u8_rgb(stride: usize, x: usize, y: usize) -> usize {
+    x * 3 + y * stride
+}
+
+/// Used by all buffers with one element per pixel
+#[inline]
+pub(crate) fn index_u32(stride: usize, x: usize, y: usize) -> usize {
+    x + y * stride
 }
 
 pub(crate) fn clear_u8(buffer: &mut GraphicsBuffer, color: Color) {
-    if let RgbaU8(buffer) = buffer {
-        buffer.chunks_exact_mut(4).for_each(|px| {
+    match buffer {
+        RgbaU8(buffer) => buffer.chunks_exact_mut(4).for_each(|px| {
             px[0] = color.r;
             px[1] = color.g;
             px[2] = color.b;
             px[3] = color.a;
-        });
-    } else {
-        panic!(
+        }),
+        GraphicsBuffer::BgraU8(buffer) => buffer.chunks_exact_mut(4).for_each(|px| {
+            px[0] = color.b;
+            px[1] = color.g;
+            px[2] = color.r;
+            px[3] = color.a;
+        }),
+        GraphicsBuffer::RgbU8(buffer) => buffer.chunks_exact_mut(3).for_each(|px| {
+            px[0] = color.r;
+            px[1] = color.g;
+            px[2] = color.b;
+        }),
+        GraphicsBuffer::GrayU8(buffer) => buffer.fill(color_to_gray(color)),
+        _ => panic!(
             "clear_u8 called on non u8 buffer, please create GitHub issue for buffer-graphics-lib"
-        )
+        ),
+    }
+}
+
+pub(crate) fn clear_u16(buffer: &mut GraphicsBuffer, color: Color) {
+    if let GraphicsBuffer::Rgb565U16(buffer) = buffer {
+        buffer.fill(color_to_rgb565(color));
+    } else {
+        panic!("clear_u16 called on non u16 buffer, please create GitHub issue for buffer-graphics-lib")
+    }
+}
+
+pub(crate) fn clear_custom(buffer: &mut GraphicsBuffer, color: Color) {
+    if let GraphicsBuffer::Custom(buffer) = buffer {
+        for idx in 0..buffer.len() {
+            buffer.set_color(idx, color);
+        }
+    } else {
+        panic!("clear_custom called on non custom buffer, please create GitHub issue for buffer-graphics-lib")
     }
 }
 
 pub(crate) fn clear_u32(buffer: &mut GraphicsBuffer, color: Color) {
     #[allow(clippy::type_complexity)] //it's internal to this method
     let result: Option<(&mut &mut [u32], fn(Color) -> u32)> = match buffer {
-        RgbaU8(_) => None,
         GraphicsBuffer::RgbaU32(buf) => Some((buf, Color::to_rgba)),
         GraphicsBuffer::ArgbU32(buf) => Some((buf, Color::to_argb)),
+        _ => None,
     };
     if let Some((buffer, method)) = result {
         let color = method(color);
@@ -68,7 +110,7 @@ impl Graphics<'_> {
     /// Convert an x,y coord to idx for use with `self.pixels`
     #[inline(always)]
     pub fn index(&self, x: usize, y: usize) -> usize {
-        (self.index_method)(self.width, x, y)
+        (self.index_method)(self.stride, x, y)
     }
 
     #[inline(always)]
@@ -81,9 +123,16 @@ impl Graphics<'_> {
         self.height
     }
 
+    /// Coordinates of this buffer's top left pixel, this is (0,0) except for bands created
+    /// by [split_bands](Graphics::split_bands) which use the coordinates of the original buffer
+    #[inline(always)]
+    pub fn origin(&self) -> Coord {
+        self.origin
+    }
+
     pub fn is_on_screen(&self, point: Coord) -> bool {
-        let x = point.x - self.translate.x;
-        let y = point.y - self.translate.y;
+        let x = point.x - self.translate.x - self.origin.x;
+        let y = point.y - self.translate.y - self.origin.y;
         x >= 0 && y >= 0 && x < self.width as isize && y < self.height as isize
     }
 }
@@ -122,9 +171,66 @@ impl Graphics<'_> {
         self.translate.y += delta.y;
     }
 
+    /// Get the current transform
+    #[inline(always)]
+    pub fn get_transform(&self) -> Transform {
+        self.transform
+    }
+
+    /// Set the transform used for shapes, lines, polylines, text positions and images
+    ///
+    /// The transform is applied before the translate. Shapes have their points transformed while
+    /// images have their area transformed (see [Transform]), images are resampled using nearest
+    /// neighbour, text glyphs are positioned by the transform but aren't resized or rotated.
+    /// [set_pixel](Graphics::set_pixel) and [clear_aware](Graphics::clear_aware) are not transformed
+    ///
+    /// # Returns
+    /// The previous transform
+    #[inline]
+    pub fn set_transform(&mut self, transform: Transform) -> Transform {
+        let old = self.transform;
+        self.transform = transform;
+        old
+    }
+
+    /// Run `method` with the transform set to `transform`, the previous transform is restored afterwards
+    #[inline]
+    pub fn with_transform<F: Fn(&mut Graphics)>(&mut self, transform: Transform, method: F) {
+        let old_transform = self.set_transform(transform);
+        method(self);
+        self.set_transform(old_transform);
+    }
+
+    /// Combine `transform` with the current transform, `transform` is applied first
+    #[inline]
+    pub fn update_transform(&mut self, transform: Transform) {
+        self.transform = transform.then(self.transform);
+    }
+
+    /// True if there's no padding between rows
+    #[inline]
+    fn is_contiguous(&self) -> bool {
+        self.stride == self.width * self.buffer.pixel_size()
+    }
+
+    /// All visible pixels, row by row
+    pub(crate) fn pixels(&self) -> Vec<Color> {
+        if self.is_contiguous() {
+            self.buffer.to_pixels()
+        } else {
+            let mut pixels = Vec::with_capacity(self.width * self.height);
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    pixels.push(self.buffer.get_color(self.index(x, y)));
+                }
+            }
+            pixels
+        }
+    }
+
     /// Copy entire pixels array to an image
     pub fn copy_to_image(&self) -> Image {
-        let pixels = self.buffer.to_pixels();
+        let pixels = self.pixels();
         Image::new(pixels, self.width, self.height)
             .expect("Copy to image failed, please create GitHub issue for buffer-graphics-lib")
     }
@@ -146,7 +252,7 @@ impl Graphics<'_> {
         }
         let width = self.width as u8;
         let height = self.height as u8;
-        let pixels = self.buffer.to_pixels();
+        let pixels = self.pixels();
         let colors: HashSet<Color> = HashSet::from_iter(pixels.iter().copied());
         let colors = if colors.len() > 255 {
             if simplify_palette {
@@ -182,25 +288,54 @@ impl Graphics<'_> {
     }
 
     /// Draw an image at `x`, `y`
-    /// If the image definitely will draw inside the window you can use [draw_image_unchecked] instead
+    ///
+    /// Opaque images are copied a row at a time when using [BlendMode::Normal] and
+    /// [CompositeMode::SourceOver], fully transparent pixels are skipped
     pub fn draw_image<P: Into<Coord>>(&mut self, xy: P, image: &Image) {
         let xy = xy.into();
-        let mut x = 0;
-        let mut y = 0;
-        for pixel in image.pixels() {
-            update_pixel(
-                &mut self.buffer,
-                &self.translate,
-                &self.clip,
-                (self.width, self.height),
-                xy.x + x as isize,
-                xy.y + y,
-                *pixel,
-            );
-            x += 1;
-            if x >= image.width() {
-                x = 0;
-                y += 1;
+        if !self.transform.is_identity() {
+            self.draw_transformed_pixels(xy, image.width(), image.height(), |x, y| {
+                image.get_pixel(x, y)
+            });
+            return;
+        }
+        let width = image.width();
+        if width == 0 {
+            return;
+        }
+        if self.composite_mode != CompositeMode::SourceOver {
+            for (i, pixel) in image.pixels().iter().enumerate() {
+                let (x, y) = ((i % width) as isize, (i / width) as isize);
+                self.update_pixel(xy.x + x, xy.y + y, *pixel);
+            }
+            return;
+        }
+        let copy_rows = self.blend_mode == BlendMode::Normal && !image.is_transparent();
+        for (y, row) in image.pixels().chunks_exact(width).enumerate() {
+            let y = xy.y + y as isize + self.translate.y;
+            let row_x = xy.x + self.translate.x;
+            match self.clip_row(y, row_x, row_x + row.len() as isize - 1) {
+                RowClip::Span(start_x, end_x) => {
+                    let visible = &row[(start_x - row_x) as usize..=(end_x - row_x) as usize];
+                    if copy_rows {
+                        self.mark_dirty_span(y, start_x, end_x);
+                        let idx = self.global_index(start_x, y);
+                        self.buffer.set_colors(idx, visible);
+                    } else {
+                        for (x, color) in (start_x..).zip(visible) {
+                            self.write_pixel(x, y, *color);
+                        }
+                    }
+                }
+                RowClip::PerPixel(start_x, end_x) => {
+                    for x in start_x..=end_x {
+                        let color = row[(x - row_x) as usize];
+                        if color.a > 0 && self.clip.is_valid((x, y)) {
+                            self.write_pixel(x, y, color);
+                        }
+                    }
+                }
+                RowClip::Empty => {}
             }
         }
     }
@@ -210,20 +345,19 @@ impl Graphics<'_> {
         let xy = xy.into();
         let palette = image.get_palette();
         let (width, height) = image.size();
+        if !self.transform.is_identity() {
+            self.draw_transformed_pixels(xy, width as usize, height as usize, |x, y| {
+                let i = image.get_pixel_index(x as u8, y as u8).unwrap();
+                palette[image.get_pixel(i).unwrap() as usize]
+            });
+            return;
+        }
         for x in 0..width {
             for y in 0..height {
                 let i = image.get_pixel_index(x, y).unwrap();
                 let color_idx = image.get_pixel(i).unwrap() as usize;
                 let color = palette[color_idx];
-                update_pixel(
-                    &mut self.buffer,
-                    &self.translate,
-                    &self.clip,
-                    (self.width, self.height),
-                    x as isize + xy.x,
-                    y as isize + xy.y,
-                    color,
-                );
+                self.update_pixel(x as isize + xy.x, y as isize + xy.y, color);
             }
         }
     }
@@ -241,20 +375,19 @@ impl Graphics<'_> {
         let palette = image.get_palette();
         let (width, height) = image.size();
         let current_frame = image.get_current_frame_pixels();
+        if !self.transform.is_identity() {
+            self.draw_transformed_pixels(xy, width as usize, height as usize, |x, y| {
+                let i = image.get_pixel_index(x as u8, y as u8).unwrap();
+                palette[current_frame[i] as usize]
+            });
+            return;
+        }
         for x in 0..width {
             for y in 0..height {
                 let i = image.get_pixel_index(x, y).unwrap();
                 let color_idx = current_frame[i] as usize;
                 let color = palette[color_idx];
-                update_pixel(
-                    &mut self.buffer,
-                    &self.translate,
-                    &self.clip,
-                    (self.width, self.height),
-                    x as isize + xy.x,
-                    y as isize + xy.y,
-                    color,
-                );
+                self.update_pixel(x as isize + xy.x, y as isize + xy.y, color);
             }
         }
     }
@@ -268,17 +401,27 @@ impl Graphics<'_> {
         close: bool,
         color: Color,
     ) {
-        for r in angle_start..=angle_end {
-            let px = Coord::from_angle(center, radius, r);
-            update_pixel(
-                &mut self.buffer,
-                &self.translate,
-                &self.clip,
-                (self.width, self.height),
-                px.x,
-                px.y,
-                color,
-            );
+        if self.transform.is_identity() && self.anti_aliasing {
+            self.plot_arc_aa(center, angle_start, angle_end, radius, color);
+        } else if self.transform.is_identity() {
+            for r in angle_start..=angle_end {
+                let px = Coord::from_angle(center, radius, r);
+                self.update_pixel(px.x, px.y, color);
+            }
+        } else {
+            let points: Vec<Coord> = (angle_start..=angle_end)
+                .map(|r| {
+                    let rads = (r as f32 - 90.0).to_radians();
+                    self.transform.apply_f32((
+                        center.x as f32 + radius as f32 * rads.cos(),
+                        center.y as f32 + radius as f32 * rads.sin(),
+                    ))
+                })
+                .map(|(x, y)| Coord::new(x.round() as isize, y.round() as isize))
+                .collect();
+            for pair in points.windows(2) {
+                self.plot_line(pair[0], pair[1], color);
+            }
         }
         if close {
             self.draw_line(
@@ -296,34 +439,27 @@ impl Graphics<'_> {
         end: P2,
         color: Color,
     ) {
-        let mut start = start.into();
-        let mut end = end.into();
+        let start = self.transform.apply(start);
+        let end = self.transform.apply(end);
+        self.plot_line(start, end, color);
+    }
+
+    /// Draw a line between two points that have already been transformed
+    pub(crate) fn plot_line(&mut self, mut start: Coord, mut end: Coord, color: Color) {
+        if self.anti_aliasing {
+            self.plot_line_aa(start, end, color);
+            return;
+        }
         if start.x > end.x || start.y > end.y {
             swap(&mut start, &mut end);
         }
         if start.x == end.x {
             for y in start.y..=end.y {
-                update_pixel(
-                    &mut self.buffer,
-                    &self.translate,
-                    &self.clip,
-                    (self.width, self.height),
-                    start.x,
-                    y,
-                    color,
-                );
+                self.update_pixel(start.x, y, color);
             }
         } else if start.y == end.y {
             for x in start.x..=end.x {
-                update_pixel(
-                    &mut self.buffer,
-                    &self.translate,
-                    &self.clip,
-                    (self.width, self.height),
-                    x,
-                    start.y,
-                    color,
-                );
+                self.update_pixel(x, start.y, color);
             }
         } else {
             let mut delta = 0;
@@ -341,15 +477,7 @@ impl Graphics<'_> {
             let mut y = y1;
             if dx >= dy {
                 loop {
-                    update_pixel(
-                        &mut self.buffer,
-                        &self.translate,
-                        &self.clip,
-                        (self.width, self.height),
-                        x,
-                        y,
-                        color,
-                    );
+                    self.update_pixel(x, y, color);
                     if x == x2 {
                         break;
                     }
@@ -362,15 +490,7 @@ impl Graphics<'_> {
                 }
             } else {
                 loop {
-                    update_pixel(
-                        &mut self.buffer,
-                        &self.translate,
-                        &self.clip,
-                        (self.width, self.height),
-                        x,
-                        y,
-                        color,
-                    );
+                    self.update_pixel(x, y, color);
                     if y == y2 {
                         break;
                     }
@@ -397,8 +517,10 @@ impl Graphics<'_> {
         renderable.render(self);
     }
 
-    /// Get the RGB values for a pixel
-    /// Alpha will always be 255
+    /// Get the color of a pixel
+    ///
+    /// The alpha is the value stored in the buffer, for formats without alpha (RGB888, RGB565
+    /// and grayscale) it will always be 255
     ///
     /// If `use_translate` is true then the x,y will be updated with `self.translate`
     ///
@@ -412,6 +534,8 @@ impl Graphics<'_> {
         } else {
             (x, y)
         };
+        let x = x - self.origin.x;
+        let y = y - self.origin.y;
 
         if x >= 0 && y >= 0 && x < self.width as isize && y < self.height as isize {
             let idx = self.index(x as usize, y as usize);
@@ -424,15 +548,25 @@ impl Graphics<'_> {
     /// Set every pixel to `color`, this ignores translate and clip
     #[inline(always)]
     pub fn clear(&mut self, color: Color) {
-        (self.clear_method)(&mut self.buffer, color);
+        if let Some(dirty) = &mut self.dirty {
+            dirty.mark_region(0, 0, self.width, self.height);
+        }
+        if self.is_contiguous() {
+            (self.clear_method)(&mut self.buffer, color);
+        } else {
+            let row_len = self.index(self.width, 0);
+            for y in 0..self.height {
+                let start = self.index(0, y);
+                (self.clear_method)(&mut self.buffer.slice_mut(start, start + row_len), color);
+            }
+        }
     }
 
     /// Set/blend every pixel with `color`, same as [clear] but this follows translate and clip
     pub fn clear_aware(&mut self, color: Color) {
-        for y in 0..self.height {
-            for x in 0..self.width {
-                self.set_pixel(x as isize, y as isize, color);
-            }
+        let right = self.origin.x + self.width as isize - 1;
+        for y in 0..self.height as isize {
+            self.fill_span(y + self.origin.y, self.origin.x, right, color);
         }
     }
 
@@ -462,8 +596,16 @@ impl Graphics<'_> {
             return;
         }
         let (width, height) = font.size();
+        let pos = if self.transform.is_identity() {
+            pos
+        } else {
+            let coord = self.transform.apply(pos);
+            (coord.x, coord.y)
+        };
 
-        let px: &[bool] = if let Some(custom) = self.custom_font.get(&code) {
+        // moved out while drawing so the glyph can be borrowed, this doesn't allocate
+        let custom_font = take(&mut self.custom_font);
+        let px: &[bool] = if let Some(custom) = custom_font.get(&code) {
             match font {
                 PixelFont::Standard4x4 => &custom.font_4x4,
                 PixelFont::Script8x8 => &custom.font_8x8,
@@ -481,18 +623,11 @@ impl Graphics<'_> {
             for y in 0..height {
                 let i = x + y * width;
                 if px[i] {
-                    update_pixel(
-                        &mut self.buffer,
-                        &self.translate,
-                        &self.clip,
-                        (self.width, self.height),
-                        x as isize + pos.0,
-                        y as isize + pos.1,
-                        color,
-                    );
+                    self.update_pixel(x as isize + pos.0, y as isize + pos.1, color);
                 }
             }
         }
+        self.custom_font = custom_font;
     }
 
     /// Should only be used by Text::render
@@ -570,164 +705,337 @@ impl Graphics<'_> {
 
     #[inline]
     pub fn draw_rect<R: Into<Rect>>(&mut self, rect: R, draw_type: DrawType) {
-        Drawable::from_obj(rect.into(), draw_type).render(self)
+        self.draw_shape(rect.into(), draw_type)
     }
 
     #[inline]
     pub fn draw_circle<C: Into<Circle>>(&mut self, circle: C, draw_type: DrawType) {
-        Drawable::from_obj(circle.into(), draw_type).render(self)
+        self.draw_shape(circle.into(), draw_type)
     }
 
     #[inline]
     pub fn draw_polygon<P: Into<Polygon>>(&mut self, polygon: P, draw_type: DrawType) {
-        Drawable::from_obj(polygon.into(), draw_type).render(self)
+        self.draw_shape(polygon.into(), draw_type)
     }
 
     #[inline]
     pub fn draw_triangle<T: Into<Triangle>>(&mut self, triangle: T, draw_type: DrawType) {
-        Drawable::from_obj(triangle.into(), draw_type).render(self)
+        self.draw_shape(triangle.into(), draw_type)
     }
 
     #[inline]
     pub fn draw_ellipse<E: Into<Ellipse>>(&mut self, ellipse: E, draw_type: DrawType) {
-        Drawable::from_obj(ellipse.into(), draw_type).render(self)
+        self.draw_shape(ellipse.into(), draw_type)
     }
 
     /// Update a pixel color, replacing or blending depending on whether `color`s alpha is 255 or not
+    /// and the current [BlendMode]
     ///
     /// If the alpha is 0 the call does nothing
     #[inline]
     pub fn set_pixel(&mut self, x: isize, y: isize, color: Color) {
-        update_pixel(
-            &mut self.buffer,
-            &self.translate,
-            &self.clip,
-            (self.width, self.height),
-            x,
-            y,
-            color,
-        );
+        self.update_pixel(x, y, color);
+    }
+
+    /// Get the current blend mode
+    #[inline(always)]
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Set how colors are combined with the existing pixels
+    ///
+    /// All drawing commands (except [clear](Graphics::clear)) will use this mode
+    ///
+    /// # Returns
+    /// The previous blend mode
+    #[inline]
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) -> BlendMode {
+        let old = self.blend_mode;
+        self.blend_mode = blend_mode;
+        old
+    }
+
+    /// Run `method` with the blend mode set to `blend_mode`, the previous mode is restored afterwards
+    #[inline]
+    pub fn with_blend_mode<F: Fn(&mut Graphics)>(&mut self, blend_mode: BlendMode, method: F) {
+        let old_mode = self.set_blend_mode(blend_mode);
+        method(self);
+        self.set_blend_mode(old_mode);
+    }
+
+    /// Get the current composite mode
+    #[inline(always)]
+    pub fn composite_mode(&self) -> CompositeMode {
+        self.composite_mode
+    }
+
+    /// Set which parts of the drawn and existing pixels are kept
+    ///
+    /// All drawing commands (except [clear](Graphics::clear)) will use this mode, for example
+    /// use [CompositeMode::Source] with a transparent color to erase pixels
+    ///
+    /// # Returns
+    /// The previous composite mode
+    #[inline]
+    pub fn set_composite_mode(&mut self, composite_mode: CompositeMode) -> CompositeMode {
+        let old = self.composite_mode;
+        self.composite_mode = composite_mode;
+        old
+    }
+
+    /// Run `method` with the composite mode set to `composite_mode`, the previous mode is restored afterwards
+    #[inline]
+    pub fn with_composite_mode<F: Fn(&mut Graphics)>(
+        &mut self,
+        composite_mode: CompositeMode,
+        method: F,
+    ) {
+        let old_mode = self.set_composite_mode(composite_mode);
+        method(self);
+        self.set_composite_mode(old_mode);
     }
 }
 
-/// Update a pixel color, using [set_pixel] or [blend_pixel] depending on whether `color`s alpha is 255 or not
-///
-/// If the alpha is 0 the call is does nothing
-fn update_pixel(
-    buffer: &mut GraphicsBuffer,
-    translate: &Coord,
-    clip: &Clip,
-    (width, height): (usize, usize),
-    x: isize,
-    y: isize,
-    color: Color,
-) {
-    let x = x + translate.x;
-    let y = y + translate.y;
-    match buffer {
-        RgbaU8(buffer) => {
-            let idx = ((x + y * width as isize) * 4) as usize;
-            if x >= 0
-                && y >= 0
-                && x < width as isize
-                && y < height as isize
-                && clip.is_valid((x, y))
-            {
-                match color.a {
-                    255 => set_pixel_u8_rgba(buffer, idx, color),
-                    0 => {}
-                    _ => blend_pixel_u8_rgba(buffer, idx, color),
+impl Graphics<'_> {
+    /// Fill `shape` a row at a time if possible, otherwise draw it pixel by pixel
+    fn draw_shape<S: Shape + Clone>(&mut self, shape: S, draw_type: DrawType)
+    where
+        Drawable<S>: CreateDrawable<S>,
+    {
+        let filled = self.transform.is_identity()
+            && !self.anti_aliasing
+            && match &draw_type {
+                DrawType::Fill(color) => self.fill_shape(&shape.to_shape_box(), *color),
+                DrawType::FillPaint(_) => {
+                    let color = PixelColor::new(&draw_type, &self.transform, self.translate);
+                    self.fill_shape_with_paint(&shape.to_shape_box(), &color)
                 }
-            }
+                _ => false,
+            };
+        if filled {
+            return;
         }
-        GraphicsBuffer::RgbaU32(buffer) => {
-            let idx = (x + y * width as isize) as usize;
-            if x >= 0
-                && y >= 0
-                && x < width as isize
-                && y < height as isize
-                && clip.is_valid((x, y))
-            {
-                match color.a {
-                    255 => set_pixel_32(buffer, idx, color, Color::to_rgba),
-                    0 => {}
-                    _ => blend_pixel_32(buffer, idx, color, Color::from_rgba, Color::to_rgba),
+        if self.transform.is_identity() {
+            Drawable::from_obj(shape, draw_type).render(self)
+        } else {
+            // the pixels from from_obj would be for the untransformed shape
+            crate::shapes::render(self, &shape, &draw_type, None)
+        }
+    }
+
+    /// Fill `shape` with `color` a row at a time, this ignores the transform
+    ///
+    /// Returns false (without drawing anything) if the shape doesn't support this, see [for_each_span]
+    pub(crate) fn fill_shape(&mut self, shape: &ShapeBox, color: Color) -> bool {
+        for_each_span(shape, |y, start_x, end_x| {
+            self.fill_span(y, start_x, end_x, color)
+        })
+    }
+
+    /// Update pixels `start_x..=end_x` on row `y`, the same as calling [update_pixel] for each
+    /// pixel but the bounds and clip (if it's a rect) are only checked once
+    ///
+    /// Opaque colors are written directly to the buffer
+    pub(crate) fn fill_span(&mut self, y: isize, start_x: isize, end_x: isize, color: Color) {
+        let y = y + self.translate.y;
+        let row_clip = self.clip_row(y, start_x + self.translate.x, end_x + self.translate.x);
+        let (start_x, end_x) = match row_clip {
+            RowClip::Span(start_x, end_x) => (start_x, end_x),
+            RowClip::PerPixel(start_x, end_x) => {
+                for x in start_x..=end_x {
+                    if self.clip.is_valid((x, y)) {
+                        self.write_pixel(x, y, color);
+                    }
                 }
+                return;
+            }
+            RowClip::Empty => return,
+        };
+        if self.composite_mode == CompositeMode::SourceOver {
+            if self.blend_mode == BlendMode::Normal && color.a == 255 {
+                self.mark_dirty_span(y, start_x, end_x);
+                let start = self.global_index(start_x, y);
+                let end = self.global_index(end_x + 1, y);
+                (self.clear_method)(&mut self.buffer.slice_mut(start, end), color);
+                return;
+            } else if color.a == 0 {
+                return;
             }
         }
-        GraphicsBuffer::ArgbU32(buffer) => {
-            let idx = (x + y * width as isize) as usize;
-            if x >= 0
-                && y >= 0
-                && x < width as isize
-                && y < height as isize
-                && clip.is_valid((x, y))
-            {
-                match color.a {
-                    255 => set_pixel_32(buffer, idx, color, Color::to_argb),
-                    0 => {}
-                    _ => blend_pixel_32(buffer, idx, color, Color::from_argb, Color::to_argb),
-                }
+        for x in start_x..=end_x {
+            self.write_pixel(x, y, color);
+        }
+    }
+
+    /// Update pixels `start_x..=end_x` on row `y` using `color` for each pixel, like
+    /// [fill_span](Graphics::fill_span) the bounds and clip (if it's a rect) are only checked once
+    pub(crate) fn fill_span_with(
+        &mut self,
+        y: isize,
+        start_x: isize,
+        end_x: isize,
+        color: &PixelColor,
+    ) {
+        if let PixelColor::Solid(color) = color {
+            self.fill_span(y, start_x, end_x, *color);
+            return;
+        }
+        let translate = self.translate;
+        let y = y + translate.y;
+        let row_clip = self.clip_row(y, start_x + translate.x, end_x + translate.x);
+        let (start_x, end_x, per_pixel) = match row_clip {
+            RowClip::Span(start_x, end_x) => (start_x, end_x, false),
+            RowClip::PerPixel(start_x, end_x) => (start_x, end_x, true),
+            RowClip::Empty => return,
+        };
+        for x in start_x..=end_x {
+            if !per_pixel || self.clip.is_valid((x, y)) {
+                self.write_pixel(x, y, color.at(x - translate.x, y - translate.y));
             }
         }
     }
-}
 
-/// Set the RGB values for a pixel
-///
-/// Generally you should use [update_pixel] instead
-///
-/// This ignores alpha, so 255,0,0,0 will draw a red pixel
-fn set_pixel_32(buffer: &mut [u32], idx: usize, color: Color, conv: fn(Color) -> u32) {
-    if idx < buffer.len() {
-        buffer[idx] = conv(color);
+    /// Restrict pixels `start_x..=end_x` on row `y` to the buffer and clip, the coords must
+    /// already be translated
+    fn clip_row(&self, y: isize, start_x: isize, end_x: isize) -> RowClip {
+        let local_y = y - self.origin.y;
+        if local_y < 0 || local_y >= self.height as isize {
+            return RowClip::Empty;
+        }
+        let start_x = start_x.max(self.origin.x);
+        let end_x = end_x.min(self.origin.x + self.width as isize - 1);
+        if start_x > end_x {
+            return RowClip::Empty;
+        }
+        match self.clip.row_span(y, start_x, end_x) {
+            RowClip::Span(start_x, end_x) if start_x > end_x => RowClip::Empty,
+            span => span,
+        }
     }
-}
 
-/// Set the RGB values for a pixel by blending it with the provided color
-/// This method uses alpha blending
-/// Generally you should use [update_pixel] instead
-fn blend_pixel_32(
-    buffer: &mut [u32],
-    idx: usize,
-    color: Color,
-    conv2: fn(u32) -> Color,
-    conv: fn(Color) -> u32,
-) {
-    let existing_color = conv2(buffer[idx]);
-    let new_color = existing_color.blend(color);
-    buffer[idx] = conv(new_color);
-}
+    /// Record pixels `start_x..=end_x` on row `y` (translated, inside the buffer) as drawn to,
+    /// if tracking
+    #[inline]
+    fn mark_dirty_span(&mut self, y: isize, start_x: isize, end_x: isize) {
+        if let Some(dirty) = &mut self.dirty {
+            dirty.mark_span(
+                (y - self.origin.y) as usize,
+                (start_x - self.origin.x) as usize,
+                (end_x - self.origin.x) as usize,
+            );
+        }
+    }
 
-/// Set the RGB values for a pixel
-///
-/// Generally you should use [update_pixel] instead
-///
-/// This ignores alpha, so 255,0,0,0 will draw a red pixel
-fn set_pixel_u8_rgba(buffer: &mut [u8], idx: usize, color: Color) {
-    if idx < buffer.len() {
-        buffer[idx] = color.r;
-        buffer[idx + 1] = color.g;
-        buffer[idx + 2] = color.b;
-        buffer[idx + 3] = color.a;
+    /// Index of translated coords that are known to be inside the buffer
+    #[inline]
+    fn global_index(&self, x: isize, y: isize) -> usize {
+        self.index((x - self.origin.x) as usize, (y - self.origin.y) as usize)
     }
-}
 
-/// Set the RGB values for a pixel by blending it with the provided color
-/// This method uses alpha blending
-/// Generally you should use [update_pixel] instead
-fn blend_pixel_u8_rgba(buffer: &mut [u8], idx: usize, color: Color) {
-    let existing_color = Color {
-        r: buffer[idx],
-        g: buffer[idx + 1],
-        b: buffer[idx + 2],
-        a: buffer[idx + 3],
-    };
-    let new_color = existing_color.blend(color);
-    buffer[idx] = new_color.r;
-    buffer[idx + 1] = new_color.g;
-    buffer[idx + 2] = new_color.b;
-    buffer[idx + 3] = new_color.a;
+    /// Draw a `width`x`height` grid of pixels at `xy` through the current transform
+    ///
+    /// Every screen pixel covered by the transformed grid is mapped back to a source pixel
+    /// (nearest neighbour) and `color_at` is called with that pixel's x,y
+    fn draw_transformed_pixels<F: Fn(usize, usize) -> Color>(
+        &mut self,
+        xy: Coord,
+        width: usize,
+        height: usize,
+        color_at: F,
+    ) {
+        let inverse = match self.transform.inverse() {
+            Some(inverse) => inverse,
+            None => return,
+        };
+        let (left, top) = (xy.x as f32, xy.y as f32);
+        let (right, bottom) = (left + width as f32, top + height as f32);
+        let corners = [(left, top), (right, top), (left, bottom), (right, bottom)]
+            .map(|corner| self.transform.apply_f32(corner));
+        let (visible_left, visible_top, visible_right, visible_bottom) = self.visible_bounds();
+        let min_x = corners.iter().map(|c| c.0).fold(f32::MAX, f32::min).floor() as isize;
+        let max_x = corners.iter().map(|c| c.0).fold(f32::MIN, f32::max).ceil() as isize;
+        let min_y = corners.iter().map(|c| c.1).fold(f32::MAX, f32::min).floor() as isize;
+        let max_y = corners.iter().map(|c| c.1).fold(f32::MIN, f32::max).ceil() as isize;
+        let (min_x, max_x) = (min_x.max(visible_left), max_x.min(visible_right + 1));
+        let (min_y, max_y) = (min_y.max(visible_top), max_y.min(visible_bottom + 1));
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let (src_x, src_y) = inverse.apply_f32((x as f32 + 0.5, y as f32 + 0.5));
+                let src_x = (src_x - left).floor();
+                let src_y = (src_y - top).floor();
+                if src_x >= 0.0 && src_y >= 0.0 && src_x < width as f32 && src_y < height as f32 {
+                    let color = color_at(src_x as usize, src_y as usize);
+                    self.update_pixel(x, y, color);
+                }
+            }
+        }
+    }
+
+    /// Area (left, top, right, bottom inclusive) in drawing coords that is inside the buffer and
+    /// the clip bounds, nothing outside this can be drawn
+    pub(crate) fn visible_bounds(&self) -> (isize, isize, isize, isize) {
+        let offset = self.origin - self.translate;
+        let (mut left, mut top) = (offset.x, offset.y);
+        let mut right = offset.x + self.width as isize - 1;
+        let mut bottom = offset.y + self.height as isize - 1;
+        if let Some(clip) = self.clip.bounds() {
+            left = left.max(clip.left() - self.translate.x);
+            top = top.max(clip.top() - self.translate.y);
+            right = right.min(clip.right() - self.translate.x);
+            bottom = bottom.min(clip.bottom() - self.translate.y);
+        }
+        (left, top, right, bottom)
+    }
+
+    /// Update a pixel color, replacing or combining it with the existing color using
+    /// [BlendMode] and [CompositeMode]
+    ///
+    /// If the alpha is 0 and the composite mode is [CompositeMode::SourceOver] the call is does nothing
+    #[inline]
+    fn update_pixel(&mut self, x: isize, y: isize, color: Color) {
+        let x = x + self.translate.x;
+        let y = y + self.translate.y;
+        let local_x = x - self.origin.x;
+        let local_y = y - self.origin.y;
+        if local_x >= 0
+            && local_y >= 0
+            && local_x < self.width as isize
+            && local_y < self.height as isize
+            && self.clip.is_valid((x, y))
+        {
+            self.write_pixel(x, y, color);
+        }
+    }
+
+    /// Replace or combine the pixel at `x`, `y` (translated, inside the buffer and clip) with
+    /// `color`, see [update_pixel]
+    ///
+    /// The pixel is only marked as dirty if it's written to
+    #[inline]
+    fn write_pixel(&mut self, x: isize, y: isize, color: Color) {
+        if self.composite_mode == CompositeMode::SourceOver && color.a == 0 {
+            return;
+        }
+        let (local_x, local_y) = ((x - self.origin.x) as usize, (y - self.origin.y) as usize);
+        if let Some(dirty) = &mut self.dirty {
+            dirty.mark(local_x, local_y);
+        }
+        let idx = self.index(local_x, local_y);
+        if self.composite_mode == CompositeMode::SourceOver {
+            if self.blend_mode == BlendMode::Normal && color.a == 255 {
+                self.buffer.set_color(idx, color);
+            } else {
+                self.buffer.blend_color(idx, color, self.blend_mode);
+            }
+        } else {
+            let existing = self.buffer.get_color(idx);
+            let new_color = self
+                .composite_mode
+                .composite(existing, color, self.blend_mode);
+            self.buffer.set_color(idx, new_color);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -789,4 +1097,298 @@ mod test {
         assert_eq!(graphics.get_pixel(10, 0, false), None);
         assert_eq!(graphics.get_pixel(0, 10, false), None);
     }
+
+    #[test]
+    fn blend_mode_applies_to_all_buffers() {
+        let dst = Color::new(200, 100, 50, 255);
+        let src = Color::new(100, 200, 50, 255);
+        let expected = Some(BlendMode::Multiply.blend(dst, src));
+        let font = PixelFont::Standard4x5;
+        let glyph = font.pixels(chr_to_code('a'));
+        let set = glyph.iter().position(|px| *px).unwrap();
+        let unset = glyph.iter().position(|px| !*px).unwrap();
+
+        let mut buf_u8 = Graphics::create_buffer_u8(8, 8);
+        let mut buf_rgba = Graphics::create_buffer_u32(8, 8);
+        let mut buf_argb = Graphics::create_buffer_u32(8, 8);
+        let graphics = [
+            Graphics::new_u8_rgba(&mut buf_u8, 8, 8).unwrap(),
+            Graphics::new_u32_rgba(&mut buf_rgba, 8, 8).unwrap(),
+            Graphics::new_u32_argb(&mut buf_argb, 8, 8).unwrap(),
+        ];
+        for mut graphics in graphics {
+            graphics.clear(dst);
+            graphics.set_blend_mode(BlendMode::Multiply);
+            graphics.draw_rect(Rect::new((0, 0), (1, 0)), fill(src));
+            assert_eq!(graphics.get_pixel(0, 0, false), expected);
+            assert_eq!(graphics.get_pixel(1, 0, false), expected);
+            assert_eq!(graphics.get_pixel(0, 1, false), Some(dst));
+
+            graphics.draw_text("a", Px(0, 2), (src, font));
+            let (x, y) = ((set % 4) as isize, (set / 4) as isize + 2);
+            assert_eq!(graphics.get_pixel(x, y, false), expected);
+            let (x, y) = ((unset % 4) as isize, (unset / 4) as isize + 2);
+            assert_eq!(graphics.get_pixel(x, y, false), Some(dst));
+
+            graphics.custom_font.insert(
+                chr_to_code('b'),
+                CustomLetter {
+                    font_4x5: [true; 20],
+                    ..CustomLetter::default()
+                },
+            );
+            graphics.draw_text("b", Px(4, 2), (src, font));
+            assert_eq!(graphics.get_pixel(4, 2, false), expected);
+            assert_eq!(graphics.get_pixel(7, 6, false), expected);
+            assert_eq!(graphics.custom_font.len(), 1);
+        }
+    }
+
+    #[test]
+    fn with_blend_mode_restores() {
+        let mut buf = Graphics::create_buffer_u8(1, 1);
+        let mut graphics = Graphics::new_u8_rgba(&mut buf, 1, 1).unwrap();
+        graphics.clear(WHITE);
+        graphics.with_blend_mode(BlendMode::Difference, |g| g.set_pixel(0, 0, WHITE));
+        assert_eq!(graphics.blend_mode(), BlendMode::Normal);
+        assert_eq!(graphics.get_pixel(0, 0, false), Some(BLACK));
+    }
+
+    #[test]
+    fn composite_mode_erases() {
+        let image = crate::make_image(4, 4, |g| {
+            g.clear(RED);
+            g.set_composite_mode(CompositeMode::Source);
+            g.draw_rect(Rect::new((1, 1), (2, 2)), fill(TRANSPARENT));
+            g.set_composite_mode(CompositeMode::DestinationOut);
+            g.set_pixel(0, 0, BLACK);
+        })
+        .unwrap();
+        assert_eq!(image.get_pixel(0, 0), TRANSPARENT);
+        assert_eq!(image.get_pixel(1, 1), TRANSPARENT);
+        assert_eq!(image.get_pixel(2, 2), TRANSPARENT);
+        assert_eq!(image.get_pixel(3, 3), RED);
+        assert!(image.is_transparent());
+    }
+
+    #[test]
+    fn transform_shapes_and_lines() {
+        let image = crate::make_image(8, 8, |g| {
+            g.set_transform(Transform::scale(2.0, 2.0));
+            g.draw_rect(Rect::new((1, 1), (2, 2)), fill(RED));
+            g.draw_line((0, 3), (3, 3), BLUE);
+        })
+        .unwrap();
+        // points are scaled, so the 2x2 rect from (1,1) becomes (2,2) to (4,4)
+        for y in 0..8 {
+            for x in 0..8 {
+                let expected = if y == 6 && x <= 6 {
+                    BLUE
+                } else if (2..=4).contains(&x) && (2..=4).contains(&y) {
+                    RED
+                } else {
+                    TRANSPARENT
+                };
+                assert_eq!(image.get_pixel(x, y), expected, "{x},{y}");
+            }
+        }
+
+        // the area of images is scaled, so a 2x2 image at (1,1) covers (2,2) to (5,5)
+        let source = Image::new(vec![RED; 4], 2, 2).unwrap();
+        let image = crate::make_image(8, 8, |g| {
+            g.set_transform(Transform::scale(2.0, 2.0));
+            g.draw_image((1, 1), &source);
+        })
+        .unwrap();
+        for y in 0..8 {
+            for x in 0..8 {
+                let expected = if (2..=5).contains(&x) && (2..=5).contains(&y) {
+                    RED
+                } else {
+                    TRANSPARENT
+                };
+                assert_eq!(image.get_pixel(x, y), expected, "{x},{y}");
+            }
+        }
+    }
+
+    #[test]
+    fn transform_images_use_nearest_neighbour() {
+        let source = Image::new(vec![RED, GREEN, BLUE, WHITE], 2, 2).unwrap();
+        let image = crate::make_image(4, 4, |g| {
+            g.set_transform(Transform::scale(2.0, 2.0));
+            g.draw_image((0, 0), &source);
+        })
+        .unwrap();
+        let expected = source.scale(Scaling::nearest_neighbour(2, 2).unwrap());
+        assert_eq!(image.pixels(), expected.pixels());
+    }
+
+    #[test]
+    fn transform_images_only_draw_visible_area() {
+        let pixels = (0..1000 * 1000)
+            .map(|i| {
+                if (i % 1000 + i / 1000) % 2 == 0 {
+                    RED
+                } else {
+                    BLUE
+                }
+            })
+            .collect();
+        let source = Image::new(pixels, 1000, 1000).unwrap();
+        let image = crate::make_image(80, 60, |g| {
+            g.set_transform(
+                Transform::scale(100.0, 100.0).then(Transform::translation(-150.0, -250.0)),
+            );
+            g.clip_mut().set_valid_rect(Rect::new((0, 0), (69, 59)));
+            g.draw_image((0, 0), &source);
+        })
+        .unwrap();
+        // (0,0) is source pixel (1,2) and (50,0) is source pixel (2,2)
+        assert_eq!(image.get_pixel(0, 0), BLUE);
+        assert_eq!(image.get_pixel(49, 49), BLUE);
+        assert_eq!(image.get_pixel(50, 0), RED);
+        assert_eq!(image.get_pixel(69, 50), BLUE);
+        assert_eq!(image.get_pixel(70, 0), TRANSPARENT);
+    }
+
+    #[test]
+    fn transform_text_position() {
+        let mut buf = Graphics::create_buffer_u8(20, 20);
+        let mut graphics = Graphics::new_u8_rgba(&mut buf, 20, 20).unwrap();
+        graphics.set_transform(Transform::translation(10.0, 5.0));
+        graphics.draw_letter((0, 0), 'I', PixelFont::Limited3x5, RED);
+        graphics.update_transform(Transform::translation(-10.0, -5.0));
+        assert!(graphics.get_transform().is_identity());
+        assert_eq!(graphics.get_pixel(11, 5, false), Some(RED));
+        assert_eq!(graphics.get_pixel(1, 0, false), Some(TRANSPARENT));
+    }
+
+    #[test]
+    fn filled_shapes_match_pixels() {
+        let shapes = [
+            Rect::new((-3, 2), (9, 7)).to_shape_box(),
+            Circle::new((6, 6), 5).to_shape_box(),
+            Ellipse::new((8, 5), 9, 6).to_shape_box(),
+            Triangle::new((1, 1), (11, 4), (3, 10)).to_shape_box(),
+            Polygon::new(&[(0, 0), (10, 2), (8, 11), (5, 4), (-1, 9)]).to_shape_box(),
+        ];
+        let mut rect_clip = Clip::new(12, 12);
+        rect_clip.set_valid_rect(Rect::new((2, 3), (8, 20)));
+        let mut complex_clip = Clip::new(12, 12);
+        complex_clip.remove_circle(Circle::new((5, 5), 3));
+        for clip in [Clip::new(12, 12), rect_clip, complex_clip] {
+            for shape in &shapes {
+                for color in [RED, BLUE.with_alpha(100)] {
+                    let prepare = |g: &mut Graphics| {
+                        g.clear(GREEN);
+                        g.set_clip(clip.clone());
+                        g.set_translate(coord!(1, -1));
+                    };
+                    let spans = crate::make_image(12, 12, |g| {
+                        prepare(g);
+                        g.draw(&Drawable::from_obj(shape.clone(), fill(color)));
+                    })
+                    .unwrap();
+                    let pixels = crate::make_image(12, 12, |g| {
+                        prepare(g);
+                        for px in shape.filled_pixels() {
+                            g.set_pixel(px.x, px.y, color);
+                        }
+                    })
+                    .unwrap();
+                    assert_eq!(spans.pixels(), pixels.pixels(), "{shape:?} {color:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn draw_image_rows_match_pixels() {
+        let opaque = Image::new((0..30).map(|i| Color::gray(i * 8)).collect(), 6, 5).unwrap();
+        let mut transparent = opaque.clone();
+        transparent.set_pixel(1, 1, TRANSPARENT);
+        transparent.set_pixel(2, 1, TRANSPARENT);
+        transparent.set_pixel(4, 3, RED.with_alpha(80));
+        let mut rect_clip = Clip::new(8, 8);
+        rect_clip.set_valid_rect(Rect::new((1, 0), (5, 6)));
+        let mut complex_clip = Clip::new(8, 8);
+        complex_clip.remove_rect(Rect::new((2, 2), (3, 3)));
+        for clip in [Clip::new(8, 8), rect_clip, complex_clip] {
+            for image in [&opaque, &transparent] {
+                for xy in [coord!(0, 0), coord!(4, -2), coord!(-3, 5)] {
+                    let prepare = |g: &mut Graphics| {
+                        g.clear(BLUE);
+                        g.set_clip(clip.clone());
+                        g.set_translate(coord!(1, 1));
+                    };
+                    let expected = crate::make_image(8, 8, |g| {
+                        prepare(g);
+                        for y in 0..image.height() {
+                            for x in 0..image.width() {
+                                let color = image.get_pixel(x, y);
+                                g.set_pixel(xy.x + x as isize, xy.y + y as isize, color);
+                            }
+                        }
+                    })
+                    .unwrap();
+                    let mut buf_u8 = Graphics::create_buffer_u8(8, 8);
+                    let mut buf_rgba = Graphics::create_buffer_u32(8, 8);
+                    let mut buf_argb = Graphics::create_buffer_u32(8, 8);
+                    let graphics = [
+                        Graphics::new_u8_rgba(&mut buf_u8, 8, 8).unwrap(),
+                        Graphics::new_u32_rgba(&mut buf_rgba, 8, 8).unwrap(),
+                        Graphics::new_u32_argb(&mut buf_argb, 8, 8).unwrap(),
+                    ];
+                    for mut graphics in graphics {
+                        prepare(&mut graphics);
+                        graphics.draw_image(xy, image);
+                        assert_eq!(graphics.copy_to_image().pixels(), expected.pixels());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn padded_rows_are_untouched() {
+        let scene = |g: &mut Graphics| {
+            g.clear(BLUE);
+            g.draw_circle(Circle::new((2, 2), 2), fill(RED));
+            g.draw_image((3, 1), &Image::new(vec![GREEN; 4], 2, 2).unwrap());
+            g.clear_aware(WHITE.with_alpha(50));
+        };
+        let expected = crate::make_image(5, 4, scene).unwrap();
+
+        let mut buf_rgb = vec![7_u8; 16 * 3 + 15];
+        let mut buf_u32 = vec![7_u32; 8 * 4];
+        let graphics = [
+            Graphics::new_with_stride(GraphicsBuffer::RgbU8(&mut buf_rgb), 5, 4, 16).unwrap(),
+            Graphics::new_with_stride(GraphicsBuffer::RgbaU32(&mut buf_u32), 5, 4, 8).unwrap(),
+        ];
+        for mut graphics in graphics {
+            scene(&mut graphics);
+            assert_eq!(
+                graphics.get_pixel(4, 3, false),
+                Some(expected.get_pixel(4, 3))
+            );
+            assert_eq!(graphics.get_pixel(5, 0, false), None);
+            assert_eq!(graphics.copy_to_image().pixels(), expected.pixels());
+        }
+        for row in buf_rgb.chunks_exact(16) {
+            assert_eq!(row[15], 7);
+        }
+        for row in buf_u32.chunks(8) {
+            assert_eq!(&row[5..], &[7, 7, 7]);
+        }
+
+        assert!(matches!(
+            Graphics::new_with_stride(GraphicsBuffer::RgbU8(&mut [0; 63]), 5, 4, 14),
+            Err(GraphicsError::InvalidStride(15, 14))
+        ));
+        assert!(matches!(
+            Graphics::new_with_stride(GraphicsBuffer::RgbU8(&mut [0; 62]), 5, 4, 16),
+            Err(GraphicsError::InvalidBufferLength(63, 62))
+        ));
+    }
 }
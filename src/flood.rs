@@ -0,0 +1,311 @@
+use crate::image::Image;
+use crate::Graphics;
+use graphics_shapes::coord::Coord;
+use ici_files::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Which neighbours of a pixel are filled by [flood_fill](Graphics::flood_fill)
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum Connectivity {
+    /// Up, down, left and right
+    #[default]
+    Four,
+    /// Up, down, left, right and diagonals
+    Eight,
+}
+
+/// What [flood_fill](Graphics::flood_fill) fills with
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum FloodColor {
+    Solid(Color),
+    /// Image repeated from 0,0 of the buffer/image, i.e. it doesn't move with the start point
+    Pattern(Image),
+}
+
+/// Settings for [Graphics::flood_fill] and [Image::flood_fill]
+///
+/// # Usage
+/// ```
+///# use buffer_graphics_lib::prelude::*;
+///# fn doc(graphics: &mut Graphics) {
+/// let fill = FloodFill::solid(RED)
+///     .with_connectivity(Connectivity::Eight)
+///     .with_tolerance(10);
+/// graphics.flood_fill((20, 20), &fill);
+///# }
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FloodFill {
+    pub color: FloodColor,
+    pub connectivity: Connectivity,
+    /// Maximum difference between any channel (including alpha) of a pixel and the start pixel
+    /// for it to be filled, 0 means only exactly matching pixels are filled
+    pub tolerance: u8,
+}
+
+impl FloodFill {
+    pub fn solid(color: Color) -> Self {
+        Self {
+            color: FloodColor::Solid(color),
+            connectivity: Connectivity::default(),
+            tolerance: 0,
+        }
+    }
+
+    pub fn pattern(image: Image) -> Self {
+        Self {
+            color: FloodColor::Pattern(image),
+            connectivity: Connectivity::default(),
+            tolerance: 0,
+        }
+    }
+
+    pub fn with_connectivity(self, connectivity: Connectivity) -> Self {
+        Self {
+            connectivity,
+            ..self
+        }
+    }
+
+    pub fn with_tolerance(self, tolerance: u8) -> Self {
+        Self { tolerance, ..self }
+    }
+
+    /// True if `color` is close enough to `target` to be filled
+    fn matches(&self, target: Color, color: Color) -> bool {
+        target.r.abs_diff(color.r) <= self.tolerance
+            && target.g.abs_diff(color.g) <= self.tolerance
+            && target.b.abs_diff(color.b) <= self.tolerance
+            && target.a.abs_diff(color.a) <= self.tolerance
+    }
+
+    /// Color for the pixel at `x`, `y` (buffer/image coords)
+    fn color_at(&self, x: isize, y: isize) -> Color {
+        match &self.color {
+            FloodColor::Solid(color) => *color,
+            FloodColor::Pattern(image) => {
+                if image.width() == 0 || image.height() == 0 {
+                    return TRANSPARENT;
+                }
+                image.get_pixel(
+                    x.rem_euclid(image.width() as isize) as usize,
+                    y.rem_euclid(image.height() as isize) as usize,
+                )
+            }
+        }
+    }
+}
+
+/// Find the connected region around `start` where `matches` is true, using scanlines
+///
+/// Returns `(y, start_x, end_x)` (inclusive) for each run of pixels, the runs don't overlap
+fn flood_spans<F: Fn(usize, usize) -> bool>(
+    width: usize,
+    height: usize,
+    start: (usize, usize),
+    connectivity: Connectivity,
+    matches: F,
+) -> Vec<(usize, usize, usize)> {
+    let mut visited = vec![false; width * height];
+    let mut spans = vec![];
+    let mut stack = vec![start];
+    while let Some((x, y)) = stack.pop() {
+        if visited[x + y * width] || !matches(x, y) {
+            continue;
+        }
+        let fillable = |x: usize, visited: &[bool]| !visited[x + y * width] && matches(x, y);
+        let mut left = x;
+        while left > 0 && fillable(left - 1, &visited) {
+            left -= 1;
+        }
+        let mut right = x;
+        while right + 1 < width && fillable(right + 1, &visited) {
+            right += 1;
+        }
+        visited[left + y * width..=right + y * width].fill(true);
+        spans.push((y, left, right));
+
+        let (scan_left, scan_right) = match connectivity {
+            Connectivity::Four => (left, right),
+            Connectivity::Eight => (left.saturating_sub(1), (right + 1).min(width - 1)),
+        };
+        for next_y in [y.checked_sub(1), Some(y + 1).filter(|y| *y < height)]
+            .into_iter()
+            .flatten()
+        {
+            // only push the first pixel of each run, the rest are found when it's expanded
+            let mut in_run = false;
+            for next_x in scan_left..=scan_right {
+                if !visited[next_x + next_y * width] && matches(next_x, next_y) {
+                    if !in_run {
+                        stack.push((next_x, next_y));
+                        in_run = true;
+                    }
+                } else {
+                    in_run = false;
+                }
+            }
+        }
+    }
+    spans
+}
+
+impl Graphics<'_> {
+    /// Fill the area around `xy` containing pixels that match the color at `xy`
+    ///
+    /// This follows translate, clip and modes (the transform is ignored), pixels outside the
+    /// clip are treated as edges. Patterns are repeated from 0,0 of the buffer
+    pub fn flood_fill<P: Into<Coord>>(&mut self, xy: P, fill: &FloodFill) {
+        let xy = xy.into() + self.translate - self.origin;
+        if xy.x < 0 || xy.y < 0 || xy.x >= self.width as isize || xy.y >= self.height as isize {
+            return;
+        }
+        let start = (xy.x as usize, xy.y as usize);
+        if !self
+            .clip
+            .is_valid((xy.x + self.origin.x, xy.y + self.origin.y))
+        {
+            return;
+        }
+        let target = self.buffer.get_color(self.index(start.0, start.1));
+        let spans = flood_spans(self.width, self.height, start, fill.connectivity, |x, y| {
+            fill.matches(target, self.buffer.get_color(self.index(x, y)))
+                && self
+                    .clip
+                    .is_valid((x as isize + self.origin.x, y as isize + self.origin.y))
+        });
+        // convert back to drawing coords
+        let offset = self.origin - self.translate;
+        for (y, start_x, end_x) in spans {
+            let y = y as isize + offset.y;
+            let (start_x, end_x) = (start_x as isize + offset.x, end_x as isize + offset.x);
+            match fill.color {
+                FloodColor::Solid(color) => self.fill_span(y, start_x, end_x, color),
+                FloodColor::Pattern(_) => {
+                    for x in start_x..=end_x {
+                        let color = fill.color_at(x + self.translate.x, y + self.translate.y);
+                        self.set_pixel(x, y, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Image {
+    /// Replace the area around `x`, `y` containing pixels that match the color at `x`, `y`
+    ///
+    /// Patterns are repeated from 0,0 of this image
+    pub fn flood_fill(&mut self, x: usize, y: usize, fill: &FloodFill) {
+        if x >= self.width() || y >= self.height() {
+            return;
+        }
+        let target = self.get_pixel(x, y);
+        let spans = flood_spans(
+            self.width(),
+            self.height(),
+            (x, y),
+            fill.connectivity,
+            |x, y| fill.matches(target, self.get_pixel(x, y)),
+        );
+        for (y, start_x, end_x) in spans {
+            for x in start_x..=end_x {
+                self.set_pixel(x, y, fill.color_at(x as isize, y as isize));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::*;
+
+    /// `#` is black, `.` is white
+    fn image(rows: &[&str]) -> Image {
+        let pixels = rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .map(|c| if c == '#' { BLACK } else { WHITE })
+            .collect();
+        Image::new(pixels, rows[0].len(), rows.len()).unwrap()
+    }
+
+    fn filled(image: &Image) -> Vec<String> {
+        image
+            .pixels()
+            .chunks_exact(image.width())
+            .map(|row| {
+                row.iter()
+                    .map(|c| match *c {
+                        BLACK => '#',
+                        WHITE => '.',
+                        RED => 'r',
+                        BLUE => 'b',
+                        _ => '?',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    const ROWS: [&str; 5] = ["..#..", ".#.#.", "#...#", ".#..#", "..##."];
+
+    #[test]
+    fn image_connectivity() {
+        let mut four = image(&ROWS);
+        four.flood_fill(2, 2, &FloodFill::solid(RED));
+        assert_eq!(filled(&four), ["..#..", ".#r#.", "#rrr#", ".#rr#", "..##."]);
+
+        let mut eight = image(&ROWS);
+        eight.flood_fill(
+            2,
+            2,
+            &FloodFill::solid(RED).with_connectivity(Connectivity::Eight),
+        );
+        assert_eq!(
+            filled(&eight),
+            ["rr#rr", "r#r#r", "#rrr#", "r#rr#", "rr##r"]
+        );
+    }
+
+    #[test]
+    fn tolerance_and_pattern() {
+        let mut image = image(&ROWS);
+        image.set_pixel(3, 3, Color::new(250, 250, 250, 255));
+        let pattern = Image::new(vec![RED, BLUE], 2, 1).unwrap();
+        image.flood_fill(2, 2, &FloodFill::pattern(pattern.clone()));
+        assert_eq!(
+            filled(&image),
+            ["..#..", ".#r#.", "#brb#", ".#r?#", "..##."]
+        );
+
+        let mut image = self::image(&ROWS);
+        image.set_pixel(3, 3, Color::new(250, 250, 250, 255));
+        image.flood_fill(2, 2, &FloodFill::pattern(pattern).with_tolerance(5));
+        assert_eq!(
+            filled(&image),
+            ["..#..", ".#r#.", "#brb#", ".#rb#", "..##."]
+        );
+    }
+
+    #[test]
+    fn graphics_follows_translate_and_clip() {
+        let mut buffer = Graphics::create_buffer_u8(5, 5);
+        let mut graphics = Graphics::new_u8_rgba(&mut buffer, 5, 5).unwrap();
+        graphics.draw_image((0, 0), &image(&ROWS));
+        graphics.set_translate(coord!(1, 1));
+        graphics
+            .clip_mut()
+            .set_valid_rect(Rect::new((0, 0), (4, 2)));
+        graphics.flood_fill((1, 1), &FloodFill::solid(RED));
+        graphics.flood_fill((10, 10), &FloodFill::solid(RED));
+        assert_eq!(
+            filled(&graphics.copy_to_image()),
+            ["..#..", ".#r#.", "#rrr#", ".#..#", "..##."]
+        );
+    }
+}
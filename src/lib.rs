@@ -17,42 +17,66 @@
 
 extern crate core;
 
+pub mod anti_aliasing;
+pub mod blending;
+pub mod canvas;
 pub mod clipping;
+pub mod diff;
+pub mod dirty;
 pub mod drawable;
 pub mod drawing;
+pub mod flood;
 pub mod image;
 #[cfg(feature = "image_loading")]
 pub mod image_loading;
 pub mod indexed;
 pub mod integration;
+pub mod paint;
+pub mod parallel;
+pub mod pixel_format;
+pub mod region;
 pub mod renderable_image;
 pub mod renderable_macros;
 pub mod scaling;
 pub mod shapes;
+pub mod state;
+pub mod stroke;
 pub mod text;
+pub mod transform;
+pub mod viewport;
 
+use crate::dirty::DirtyTiles;
 use crate::prelude::*;
-use crate::GraphicsError::InvalidBufferLength;
+use crate::state::GraphicsState;
+use crate::GraphicsError::{InvalidBufferLength, InvalidStride};
 use fnv::FnvHashMap;
 use thiserror::Error;
 
 pub mod prelude {
+    pub use crate::blending::*;
+    pub use crate::canvas::*;
     pub use crate::clipping::*;
+    pub use crate::diff::*;
     pub use crate::drawable::*;
     pub use crate::drawing::*;
+    pub use crate::flood::*;
     pub use crate::image::*;
     #[cfg(feature = "image_loading")]
     pub use crate::image_loading::*;
     pub use crate::indexed::*;
     #[allow(unused_imports)]
     pub use crate::integration::*;
+    pub use crate::paint::*;
+    pub use crate::pixel_format::*;
     pub use crate::shapes::collection::*;
     pub use crate::shapes::polyline::*;
     pub use crate::shapes::*;
+    pub use crate::stroke::*;
     pub use crate::text::format::*;
     pub use crate::text::pos::*;
     pub use crate::text::wrapping::*;
     pub use crate::text::*;
+    pub use crate::transform::Transform;
     pub use crate::CustomLetter;
     pub use crate::Graphics;
     pub use crate::GraphicsError;
@@ -78,12 +102,28 @@ pub enum GraphicsError {
     TooBig(usize, usize),
     #[error("Creating image")]
     ImageError(IndexedImageError),
+    #[error("Viewport {0},{1} {2}x{3} is outside of the buffer ({4}x{5})")]
+    ViewportOutOfBounds(isize, isize, usize, usize, usize, usize),
+    #[error("Stride must be at least the width of a row, row: {0}, stride: {1}")]
+    InvalidStride(usize, usize),
+    #[error("Frames must be the same size to diff, expected: {0}x{1}, found: {2}x{3}")]
+    DiffSize(usize, usize, usize, usize),
 }
 
 pub enum GraphicsBuffer<'a> {
     RgbaU8(&'a mut [u8]),
     RgbaU32(&'a mut [u32]),
     ArgbU32(&'a mut [u32]),
+    /// 4 bytes per pixel in the order blue, green, red, alpha (used by Linux framebuffers)
+    BgraU8(&'a mut [u8]),
+    /// 3 bytes per pixel, alpha is discarded
+    RgbU8(&'a mut [u8]),
+    /// 5 bits red, 6 bits green, 5 bits blue per pixel, alpha is discarded
+    Rgb565U16(&'a mut [u16]),
+    /// 1 byte (luminance) per pixel, alpha is discarded
+    GrayU8(&'a mut [u8]),
+    /// Any other format, see [PixelFormat]
+    Custom(CustomBuffer<'a>),
 }
 
 impl GraphicsBuffer<'_> {
@@ -95,14 +135,31 @@ impl GraphicsBuffer<'_> {
                 .collect(),
             GraphicsBuffer::RgbaU32(buf) => buf.iter().copied().map(Color::from_rgba).collect(),
             GraphicsBuffer::ArgbU32(buf) => buf.iter().copied().map(Color::from_argb).collect(),
+            GraphicsBuffer::BgraU8(buf) => buf
+                .chunks_exact(4)
+                .map(|p| Color::new(p[2], p[1], p[0], p[3]))
+                .collect(),
+            GraphicsBuffer::RgbU8(buf) => buf
+                .chunks_exact(3)
+                .map(|p| Color::new(p[0], p[1], p[2], 255))
+                .collect(),
+            GraphicsBuffer::Rgb565U16(buf) => buf.iter().copied().map(rgb565_to_color).collect(),
+            GraphicsBuffer::GrayU8(buf) => buf.iter().copied().map(gray_to_color).collect(),
+            GraphicsBuffer::Custom(buf) => (0..buf.len()).map(|i| buf.get_color(i)).collect(),
         }
     }
 
+    /// Number of elements per pixel
     pub const fn pixel_size(&self) -> usize {
         match self {
             GraphicsBuffer::RgbaU8(_) => 4,
             GraphicsBuffer::RgbaU32(_) => 1,
             GraphicsBuffer::ArgbU32(_) => 1,
+            GraphicsBuffer::BgraU8(_) => 4,
+            GraphicsBuffer::RgbU8(_) => 3,
+            GraphicsBuffer::Rgb565U16(_) => 1,
+            GraphicsBuffer::GrayU8(_) => 1,
+            GraphicsBuffer::Custom(_) => 1,
         }
     }
 
@@ -113,6 +170,146 @@ impl GraphicsBuffer<'_> {
             }
             GraphicsBuffer::RgbaU32(buf) => Color::from_rgba(buf[idx]),
             GraphicsBuffer::ArgbU32(buf) => Color::from_argb(buf[idx]),
+            GraphicsBuffer::BgraU8(buf) => {
+                Color::new(buf[idx + 2], buf[idx + 1], buf[idx], buf[idx + 3])
+            }
+            GraphicsBuffer::RgbU8(buf) => Color::new(buf[idx], buf[idx + 1], buf[idx + 2], 255),
+            GraphicsBuffer::Rgb565U16(buf) => rgb565_to_color(buf[idx]),
+            GraphicsBuffer::GrayU8(buf) => gray_to_color(buf[idx]),
+            GraphicsBuffer::Custom(buf) => buf.get_color(idx),
+        }
+    }
+
+    pub fn set_color(&mut self, idx: usize, color: Color) {
+        match self {
+            GraphicsBuffer::RgbaU8(buf) => {
+                buf[idx] = color.r;
+                buf[idx + 1] = color.g;
+                buf[idx + 2] = color.b;
+                buf[idx + 3] = color.a;
+            }
+            GraphicsBuffer::RgbaU32(buf) => buf[idx] = color.to_rgba(),
+            GraphicsBuffer::ArgbU32(buf) => buf[idx] = color.to_argb(),
+            GraphicsBuffer::BgraU8(buf) => {
+                buf[idx] = color.b;
+                buf[idx + 1] = color.g;
+                buf[idx + 2] = color.r;
+                buf[idx + 3] = color.a;
+            }
+            GraphicsBuffer::RgbU8(buf) => {
+                buf[idx] = color.r;
+                buf[idx + 1] = color.g;
+                buf[idx + 2] = color.b;
+            }
+            GraphicsBuffer::Rgb565U16(buf) => buf[idx] = color_to_rgb565(color),
+            GraphicsBuffer::GrayU8(buf) => buf[idx] = color_to_gray(color),
+            GraphicsBuffer::Custom(buf) => buf.set_color(idx, color),
+        }
+    }
+
+    /// Combine `color` with the pixel at `idx` using `blend_mode`
+    pub(crate) fn blend_color(&mut self, idx: usize, color: Color, blend_mode: BlendMode) {
+        if let GraphicsBuffer::Custom(buf) = self {
+            buf.blend_color(idx, color, blend_mode);
+        } else {
+            let existing = self.get_color(idx);
+            self.set_color(idx, blend_mode.blend(existing, color));
+        }
+    }
+
+    /// Write `colors` to consecutive pixels starting at element (not pixel) index `idx`
+    pub(crate) fn set_colors(&mut self, idx: usize, colors: &[Color]) {
+        match self {
+            GraphicsBuffer::RgbaU8(buf) => buf[idx..idx + colors.len() * 4]
+                .chunks_exact_mut(4)
+                .zip(colors)
+                .for_each(|(px, color)| px.copy_from_slice(&[color.r, color.g, color.b, color.a])),
+            GraphicsBuffer::RgbaU32(buf) => buf[idx..idx + colors.len()]
+                .iter_mut()
+                .zip(colors)
+                .for_each(|(px, color)| *px = color.to_rgba()),
+            GraphicsBuffer::ArgbU32(buf) => buf[idx..idx + colors.len()]
+                .iter_mut()
+                .zip(colors)
+                .for_each(|(px, color)| *px = color.to_argb()),
+            _ => {
+                let size = self.pixel_size();
+                for (i, color) in colors.iter().enumerate() {
+                    self.set_color(idx + i * size, *color);
+                }
+            }
+        }
+    }
+
+    /// Copy elements (not pixels) `start..end` to `dest`, the ranges can overlap
+    pub(crate) fn copy_within(&mut self, start: usize, end: usize, dest: usize) {
+        match self {
+            GraphicsBuffer::RgbaU8(buf) => buf.copy_within(start..end, dest),
+            GraphicsBuffer::RgbaU32(buf) => buf.copy_within(start..end, dest),
+            GraphicsBuffer::ArgbU32(buf) => buf.copy_within(start..end, dest),
+            GraphicsBuffer::BgraU8(buf) => buf.copy_within(start..end, dest),
+            GraphicsBuffer::RgbU8(buf) => buf.copy_within(start..end, dest),
+            GraphicsBuffer::Rgb565U16(buf) => buf.copy_within(start..end, dest),
+            GraphicsBuffer::GrayU8(buf) => buf.copy_within(start..end, dest),
+            GraphicsBuffer::Custom(buf) => {
+                let len = end - start;
+                for i in 0..len {
+                    // copy backwards when moving forwards so pixels aren't overwritten before they're read
+                    let i = if dest > start { len - 1 - i } else { i };
+                    let color = buf.get_color(start + i);
+                    buf.set_color(dest + i, color);
+                }
+            }
+        }
+    }
+
+    /// Number of elements (not pixels) in the buffer
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            GraphicsBuffer::RgbaU8(buf) => buf.len(),
+            GraphicsBuffer::RgbaU32(buf) => buf.len(),
+            GraphicsBuffer::ArgbU32(buf) => buf.len(),
+            GraphicsBuffer::BgraU8(buf) => buf.len(),
+            GraphicsBuffer::RgbU8(buf) => buf.len(),
+            GraphicsBuffer::Rgb565U16(buf) => buf.len(),
+            GraphicsBuffer::GrayU8(buf) => buf.len(),
+            GraphicsBuffer::Custom(buf) => buf.len(),
+        }
+    }
+
+    /// Split the buffer into two at element (not pixel) index `mid`
+    ///
+    /// [Custom](GraphicsBuffer::Custom) buffers can't be split and are returned unchanged
+    pub(crate) fn split_at(self, mid: usize) -> Result<(Self, Self), Self> {
+        macro_rules! split {
+            ($variant: path, $buf: expr) => {{
+                let (lhs, rhs) = $buf.split_at_mut(mid);
+                Ok(($variant(lhs), $variant(rhs)))
+            }};
+        }
+        match self {
+            GraphicsBuffer::RgbaU8(buf) => split!(GraphicsBuffer::RgbaU8, buf),
+            GraphicsBuffer::RgbaU32(buf) => split!(GraphicsBuffer::RgbaU32, buf),
+            GraphicsBuffer::ArgbU32(buf) => split!(GraphicsBuffer::ArgbU32, buf),
+            GraphicsBuffer::BgraU8(buf) => split!(GraphicsBuffer::BgraU8, buf),
+            GraphicsBuffer::RgbU8(buf) => split!(GraphicsBuffer::RgbU8, buf),
+            GraphicsBuffer::Rgb565U16(buf) => split!(GraphicsBuffer::Rgb565U16, buf),
+            GraphicsBuffer::GrayU8(buf) => split!(GraphicsBuffer::GrayU8, buf),
+            GraphicsBuffer::Custom(buf) => Err(GraphicsBuffer::Custom(buf)),
+        }
+    }
+
+    /// Reborrow part of the buffer, `start` and `end` are element (not pixel) indexes
+    pub(crate) fn slice_mut(&mut self, start: usize, end: usize) -> GraphicsBuffer<'_> {
+        match self {
+            GraphicsBuffer::RgbaU8(buf) => GraphicsBuffer::RgbaU8(&mut buf[start..end]),
+            GraphicsBuffer::RgbaU32(buf) => GraphicsBuffer::RgbaU32(&mut buf[start..end]),
+            GraphicsBuffer::ArgbU32(buf) => GraphicsBuffer::ArgbU32(&mut buf[start..end]),
+            GraphicsBuffer::BgraU8(buf) => GraphicsBuffer::BgraU8(&mut buf[start..end]),
+            GraphicsBuffer::RgbU8(buf) => GraphicsBuffer::RgbU8(&mut buf[start..end]),
+            GraphicsBuffer::Rgb565U16(buf) => GraphicsBuffer::Rgb565U16(&mut buf[start..end]),
+            GraphicsBuffer::GrayU8(buf) => GraphicsBuffer::GrayU8(&mut buf[start..end]),
+            GraphicsBuffer::Custom(buf) => GraphicsBuffer::Custom(buf.slice_mut(start, end)),
         }
     }
 }
@@ -121,10 +318,22 @@ pub struct Graphics<'buffer> {
     buffer: GraphicsBuffer<'buffer>,
     width: usize,
     height: usize,
+    ///Number of elements per row in `buffer`, this is larger than the row for viewports and padded buffers
+    stride: usize,
+    ///Coordinates of the top left pixel of `buffer`, only non zero for bands
+    origin: Coord,
     ///Offsets all drawing commands
     translate: Coord,
+    ///Applied to shapes, lines, text positions and images before `translate`
+    transform: Transform,
     ///Used to restrict drawing to inside this region, initially includes the whole screen
     clip: Clip,
+    ///How colors are combined with the existing pixels, see [BlendMode]
+    blend_mode: BlendMode,
+    ///Which parts of the drawn and existing pixels are kept, see [CompositeMode]
+    composite_mode: CompositeMode,
+    ///If true lines and shapes have smooth edges, see [Graphics::set_anti_aliasing]
+    anti_aliasing: bool,
     /// Allows you to replace any supported ASCII with a custom glyph
     /// To replace 'a' with '█' for 4x5 fonts (such as `Standard4x5`) write
     ///
@@ -136,10 +345,19 @@ pub struct Graphics<'buffer> {
     ///
     /// Note: `A-Za-z0-9!@$%^&*(),./;'\\[]<>?:\"{}_+~#…¤£¥¢✓|€` are valid for [text::chr_to_code]
     pub custom_font: FnvHashMap<u8, CustomLetter>,
-    index_method: fn(usize, usize, usize) -> usize,
-    clear_method: fn(&mut GraphicsBuffer, Color),
+    ///Saved settings, see [Graphics::save]
+    state_stack: Vec<GraphicsState>,
+    ///Tiles drawn to since last checked, see [Graphics::track_dirty_regions]
+    dirty: Option<DirtyTiles>,
+    index_method: IndexMethod,
+    clear_method: ClearMethod,
 }
 
+///Converts stride, x, y to an index in the buffer
+type IndexMethod = fn(usize, usize, usize) -> usize;
+///Sets every pixel in the buffer
+type ClearMethod = fn(&mut GraphicsBuffer, Color);
+
 impl Graphics<'_> {
     /// Create a buffer of the correct size
     #[inline]
@@ -152,6 +370,24 @@ impl Graphics<'_> {
     pub fn create_buffer_u8(width: usize, height: usize) -> Vec<u8> {
         vec![0; width * height * 4]
     }
+
+    /// Create a buffer of the correct size for [Graphics::new_u8_rgb]
+    #[inline]
+    pub fn create_buffer_u8_rgb(width: usize, height: usize) -> Vec<u8> {
+        vec![0; width * height * 3]
+    }
+
+    /// Create a buffer of the correct size for [Graphics::new_u8_gray]
+    #[inline]
+    pub fn create_buffer_u8_gray(width: usize, height: usize) -> Vec<u8> {
+        vec![0; width * height]
+    }
+
+    /// Create a buffer of the correct size for [Graphics::new_u16_rgb565]
+    #[inline]
+    pub fn create_buffer_u16(width: usize, height: usize) -> Vec<u16> {
+        vec![0; width * height]
+    }
 }
 
 /// Only the letter sizes you'll use need to be set
@@ -241,21 +477,7 @@ impl<'buffer> Graphics<'_> {
         width: usize,
         height: usize,
     ) -> Result<Graphics<'buffer>, GraphicsError> {
-        let count = width * height * 4;
-        if count != buffer.len() {
-            return Err(InvalidBufferLength(count, buffer.len()));
-        }
-        let buffer = GraphicsBuffer::RgbaU8(buffer);
-        Ok(Graphics {
-            buffer,
-            width,
-            height,
-            translate: Coord::default(),
-            clip: Clip::new(width, height),
-            custom_font: FnvHashMap::default(),
-            clear_method: clear_u8,
-            index_method: index_u8,
-        })
+        Graphics::new_with_stride(GraphicsBuffer::RgbaU8(buffer), width, height, width * 4)
     }
 
     /// `buffer` needs to be `width * height` long
@@ -266,21 +488,7 @@ impl<'buffer> Graphics<'_> {
         width: usize,
         height: usize,
     ) -> Result<Graphics<'buffer>, GraphicsError> {
-        let count = width * height;
-        if count != buffer.len() {
-            return Err(InvalidBufferLength(count, buffer.len()));
-        }
-        let buffer = GraphicsBuffer::RgbaU32(buffer);
-        Ok(Graphics {
-            buffer,
-            width,
-            height,
-            translate: Coord::default(),
-            clip: Clip::new(width, height),
-            custom_font: FnvHashMap::default(),
-            clear_method: clear_u32,
-            index_method: index_u32,
-        })
+        Graphics::new_with_stride(GraphicsBuffer::RgbaU32(buffer), width, height, width)
     }
 
     /// `buffer` needs to be `width * height` long
@@ -291,20 +499,165 @@ impl<'buffer> Graphics<'_> {
         width: usize,
         height: usize,
     ) -> Result<Graphics<'buffer>, GraphicsError> {
-        let count = width * height;
-        if count != buffer.len() {
-            return Err(InvalidBufferLength(count, buffer.len()));
+        Graphics::new_with_stride(GraphicsBuffer::ArgbU32(buffer), width, height, width)
+    }
+
+    /// `buffer` needs to be `width * height * 4` long, with the bytes of each pixel in the
+    /// order blue, green, red, alpha
+    ///
+    /// You can use [Graphics::create_buffer_u8] to guarantee the correct size
+    pub fn new_u8_bgra(
+        buffer: &'buffer mut [u8],
+        width: usize,
+        height: usize,
+    ) -> Result<Graphics<'buffer>, GraphicsError> {
+        Graphics::new_with_stride(GraphicsBuffer::BgraU8(buffer), width, height, width * 4)
+    }
+
+    /// `buffer` needs to be `width * height * 3` long, alpha is discarded when drawing
+    /// (after blending) and pixels are always read as opaque
+    ///
+    /// You can use [Graphics::create_buffer_u8_rgb] to guarantee the correct size
+    pub fn new_u8_rgb(
+        buffer: &'buffer mut [u8],
+        width: usize,
+        height: usize,
+    ) -> Result<Graphics<'buffer>, GraphicsError> {
+        Graphics::new_with_stride(GraphicsBuffer::RgbU8(buffer), width, height, width * 3)
+    }
+
+    /// `buffer` needs to be `width * height` long, alpha is discarded when drawing
+    /// (after blending) and pixels are always read as opaque
+    ///
+    /// You can use [Graphics::create_buffer_u16] to guarantee the correct size
+    pub fn new_u16_rgb565(
+        buffer: &'buffer mut [u16],
+        width: usize,
+        height: usize,
+    ) -> Result<Graphics<'buffer>, GraphicsError> {
+        Graphics::new_with_stride(GraphicsBuffer::Rgb565U16(buffer), width, height, width)
+    }
+
+    /// `buffer` needs to be `width * height` long, colors are converted to their luminance
+    /// and pixels are always read as opaque
+    ///
+    /// You can use [Graphics::create_buffer_u8_gray] to guarantee the correct size
+    pub fn new_u8_gray(
+        buffer: &'buffer mut [u8],
+        width: usize,
+        height: usize,
+    ) -> Result<Graphics<'buffer>, GraphicsError> {
+        Graphics::new_with_stride(GraphicsBuffer::GrayU8(buffer), width, height, width)
+    }
+
+    /// Draw to a buffer in any format, see [PixelFormat]
+    ///
+    /// Boxed formats can be used with `Graphics::new_custom(&mut *boxed, width, height)`
+    ///
+    /// # Errors
+    /// * `GraphicsError::InvalidStride` if `format.stride()` is less than `width`
+    /// * `GraphicsError::InvalidBufferLength` if `format.byte_len()` is too short, the values are
+    ///   in bytes
+    pub fn new_custom(
+        format: &'buffer mut (dyn PixelFormat + Send + 'buffer),
+        width: usize,
+        height: usize,
+    ) -> Result<Graphics<'buffer>, GraphicsError> {
+        let stride = format.stride();
+        if stride < width {
+            return Err(InvalidStride(width, stride));
+        }
+        if let Some(len) = format.byte_len() {
+            let pixels = if height == 0 {
+                0
+            } else {
+                stride * (height - 1) + width
+            };
+            let needed = match format.bytes_per_pixel() {
+                0 => pixels.div_ceil(8),
+                bytes => pixels * bytes,
+            };
+            if len < needed {
+                return Err(InvalidBufferLength(needed, len));
+            }
+        }
+        Graphics::new_with_stride(
+            GraphicsBuffer::Custom(CustomBuffer::new(format, height)),
+            width,
+            height,
+            stride,
+        )
+    }
+
+    /// Use a buffer that has padding at the end of each row, such as a framebuffer or texture
+    ///
+    /// `stride` is the number of elements (i.e. bytes for `u8` buffers) from the start of one
+    /// row to the start of the next, the padding is never read or written. The buffer needs to be
+    /// at least `stride * (height - 1) + width * pixel size` long
+    ///
+    /// If `stride` is the same as the width (in elements) then this is the same as the other
+    /// constructors, i.e. the buffer needs to be exactly the right size
+    ///
+    /// # Usage
+    /// ```
+    ///# use buffer_graphics_lib::prelude::*;
+    ///# use buffer_graphics_lib::GraphicsBuffer;
+    /// // 5x4 RGB image with each row padded to 16 bytes
+    /// let mut buffer = vec![0_u8; 64];
+    /// let mut graphics = Graphics::new_with_stride(GraphicsBuffer::RgbU8(&mut buffer), 5, 4, 16).unwrap();
+    /// graphics.clear(WHITE);
+    /// assert_eq!(buffer[15], 0);
+    /// ```
+    ///
+    /// # Errors
+    /// * `GraphicsError::InvalidStride` if `stride` is less than the width (in elements)
+    /// * `GraphicsError::InvalidBufferLength` if the buffer is too short
+    pub fn new_with_stride(
+        buffer: GraphicsBuffer<'buffer>,
+        width: usize,
+        height: usize,
+        stride: usize,
+    ) -> Result<Graphics<'buffer>, GraphicsError> {
+        let row_len = width * buffer.pixel_size();
+        if stride < row_len {
+            return Err(InvalidStride(row_len, stride));
+        }
+        if stride == row_len {
+            let count = row_len * height;
+            if count != buffer.len() {
+                return Err(InvalidBufferLength(count, buffer.len()));
+            }
+        } else {
+            let count = stride * height.saturating_sub(1) + row_len;
+            if count > buffer.len() {
+                return Err(InvalidBufferLength(count, buffer.len()));
+            }
         }
-        let buffer = GraphicsBuffer::ArgbU32(buffer);
+        let (index_method, clear_method): (IndexMethod, ClearMethod) = match &buffer {
+            GraphicsBuffer::RgbaU8(_) | GraphicsBuffer::BgraU8(_) => (index_u8, clear_u8),
+            GraphicsBuffer::RgbU8(_) => (index_u8_rgb, clear_u8),
+            GraphicsBuffer::GrayU8(_) => (index_u32, clear_u8),
+            GraphicsBuffer::RgbaU32(_) | GraphicsBuffer::ArgbU32(_) => (index_u32, clear_u32),
+            GraphicsBuffer::Rgb565U16(_) => (index_u32, clear_u16),
+            GraphicsBuffer::Custom(_) => (index_u32, clear_custom),
+        };
         Ok(Graphics {
             buffer,
             width,
             height,
+            stride,
+            origin: Coord::default(),
             translate: Coord::default(),
+            transform: Transform::IDENTITY,
             clip: Clip::new(width, height),
+            blend_mode: BlendMode::default(),
+            composite_mode: CompositeMode::default(),
+            anti_aliasing: false,
             custom_font: FnvHashMap::default(),
-            clear_method: clear_u32,
-            index_method: index_u32,
+            state_stack: vec![],
+            dirty: None,
+            clear_method,
+            index_method,
         })
     }
 }
@@ -0,0 +1,623 @@
+use crate::drawable::DrawType;
+use crate::image::Image;
+use crate::transform::Transform;
+use crate::Graphics;
+use graphics_shapes::coord::Coord;
+use graphics_shapes::shape_box::ShapeBox;
+use ici_files::image::IndexedImage;
+use ici_files::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// What a [Gradient] does outside of its first and last stops
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum GradientRepeat {
+    /// Use the color of the nearest end
+    #[default]
+    Pad,
+    /// Start again from the first stop
+    Repeat,
+    /// Go back and forth between the first and last stops
+    Reflect,
+}
+
+/// Colors at positions between 0.0 and 1.0, used by [Paint]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    stops: Vec<(f32, Color)>,
+    repeat: GradientRepeat,
+}
+
+impl Gradient {
+    /// Create a gradient from `(position, color)` stops, positions are clamped to 0.0..=1.0 and
+    /// stops are sorted by position
+    pub fn new(stops: &[(f32, Color)]) -> Self {
+        let mut stops: Vec<(f32, Color)> = stops
+            .iter()
+            .map(|(position, color)| (position.clamp(0.0, 1.0), *color))
+            .collect();
+        stops.sort_by(|lhs, rhs| lhs.0.total_cmp(&rhs.0));
+        Self {
+            stops,
+            repeat: GradientRepeat::default(),
+        }
+    }
+
+    /// Gradient from `start` to `end`
+    pub fn between(start: Color, end: Color) -> Self {
+        Gradient::new(&[(0.0, start), (1.0, end)])
+    }
+
+    pub fn with_repeat(self, repeat: GradientRepeat) -> Self {
+        Self { repeat, ..self }
+    }
+
+    #[inline(always)]
+    pub fn stops(&self) -> &[(f32, Color)] {
+        &self.stops
+    }
+
+    #[inline(always)]
+    pub fn repeat(&self) -> GradientRepeat {
+        self.repeat
+    }
+
+    /// Color at `position`, where 0.0 is the start and 1.0 is the end of the gradient
+    pub fn color_at(&self, position: f32) -> Color {
+        let position = match self.repeat {
+            GradientRepeat::Pad => position.clamp(0.0, 1.0),
+            GradientRepeat::Repeat => position.rem_euclid(1.0),
+            GradientRepeat::Reflect => {
+                let position = position.rem_euclid(2.0);
+                if position > 1.0 {
+                    2.0 - position
+                } else {
+                    position
+                }
+            }
+        };
+        let Some(first) = self.stops.first() else {
+            return TRANSPARENT;
+        };
+        if position <= first.0 {
+            return first.1;
+        }
+        for pair in self.stops.windows(2) {
+            let ((start, from), (end, to)) = (pair[0], pair[1]);
+            if position <= end {
+                if end - start <= f32::EPSILON {
+                    return to;
+                }
+                return lerp(from, to, (position - start) / (end - start));
+            }
+        }
+        self.stops[self.stops.len() - 1].1
+    }
+}
+
+fn lerp(from: Color, to: Color, amount: f32) -> Color {
+    let channel =
+        |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * amount).round() as u8;
+    Color::new(
+        channel(from.r, to.r),
+        channel(from.g, to.g),
+        channel(from.b, to.b),
+        channel(from.a, to.a),
+    )
+}
+
+/// Pixel patterns used by [Paint::Dither]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum DitherPattern {
+    /// Ordered dither using a 2x2 Bayer matrix (5 levels)
+    Bayer2,
+    /// Ordered dither using a 4x4 Bayer matrix (17 levels)
+    Bayer4,
+    /// Ordered dither using a 8x8 Bayer matrix (65 levels)
+    Bayer8,
+    /// Alternating pixels, density is ignored
+    Checkerboard,
+    /// Diagonal lines going up and to the right, this many pixels apart, density is ignored
+    DiagonalHatch(usize),
+}
+
+impl DitherPattern {
+    /// True if the pixel at `x`, `y` (screen coords) should be the foreground color
+    pub fn is_foreground(&self, x: isize, y: isize, density: f32) -> bool {
+        let size = match self {
+            DitherPattern::Bayer2 => 2,
+            DitherPattern::Bayer4 => 4,
+            DitherPattern::Bayer8 => 8,
+            DitherPattern::Checkerboard => return (x + y).rem_euclid(2) == 0,
+            DitherPattern::DiagonalHatch(spacing) => {
+                return (x + y).rem_euclid((*spacing).max(1) as isize) == 0
+            }
+        };
+        let threshold = bayer(
+            x.rem_euclid(size) as usize,
+            y.rem_euclid(size) as usize,
+            size as usize,
+        );
+        (threshold as f32 + 0.5) / ((size * size) as f32) < density
+    }
+}
+
+/// Value of a `size`x`size` Bayer matrix at `x`, `y`
+fn bayer(x: usize, y: usize, size: usize) -> usize {
+    const BAYER2: [[usize; 2]; 2] = [[0, 2], [3, 1]];
+    if size <= 1 {
+        return 0;
+    }
+    let half = size / 2;
+    4 * bayer(x % half, y % half, half) + BAYER2[y / half][x / half]
+}
+
+/// Colors that vary across a shape, used with [DrawType::FillPaint]
+///
+/// Positions are in the same coordinates as the shape, so the paint moves with the translate and
+/// transform (except for [Paint::Dither])
+///
+/// # Usage
+/// ```
+///# use buffer_graphics_lib::prelude::*;
+///# fn doc(graphics: &mut Graphics) {
+/// let sky = Paint::linear((0, 0), (0, 100), Gradient::between(BLUE, WHITE));
+/// graphics.draw_rect(Rect::new((0, 0), (200, 100)), fill_paint(sky));
+///
+/// let glow = Gradient::new(&[(0.0, WHITE), (0.5, YELLOW), (1.0, YELLOW.with_alpha(0))]);
+/// graphics.draw_circle(Circle::new((150, 30), 20), fill_paint(Paint::radial((150, 30), 20, glow)));
+///# }
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Paint {
+    /// Gradient along the line from `start` (0.0) to `end` (1.0), constant across the line
+    LinearGradient {
+        start: Coord,
+        end: Coord,
+        gradient: Gradient,
+    },
+    /// Gradient from `center` (0.0) to `radius` (1.0)
+    RadialGradient {
+        center: Coord,
+        radius: usize,
+        gradient: Gradient,
+    },
+    /// `image` repeated in every direction, the top left of one copy is at `offset` and each
+    /// image pixel is `scale` pixels wide and high
+    ///
+    /// The image is shared, so cloning the paint (or a [DrawType] using it) doesn't copy the pixels
+    ImagePattern {
+        image: Arc<Image>,
+        offset: Coord,
+        scale: f32,
+    },
+    /// Mix of `background` and `foreground`, where `density` (0.0 to 1.0) is roughly how many
+    /// pixels are `foreground`
+    ///
+    /// This is anchored to the screen (the translate and transform are ignored) so the patterns
+    /// of shapes next to each other line up
+    Dither {
+        pattern: DitherPattern,
+        density: f32,
+        background: Color,
+        foreground: Color,
+    },
+}
+
+impl Paint {
+    pub fn linear<P1: Into<Coord>, P2: Into<Coord>>(
+        start: P1,
+        end: P2,
+        gradient: Gradient,
+    ) -> Self {
+        Paint::LinearGradient {
+            start: start.into(),
+            end: end.into(),
+            gradient,
+        }
+    }
+
+    pub fn radial<P: Into<Coord>>(center: P, radius: usize, gradient: Gradient) -> Self {
+        Paint::RadialGradient {
+            center: center.into(),
+            radius,
+            gradient,
+        }
+    }
+
+    /// Tile `image`, see [Paint::ImagePattern]
+    ///
+    /// # Usage
+    /// ```
+    ///# use buffer_graphics_lib::prelude::*;
+    ///# fn doc(graphics: &mut Graphics, bricks: Image) {
+    /// let wall = Paint::pattern(bricks, (0, 0), 2.0);
+    /// graphics.draw_polygon(Polygon::new(&[(10, 10), (60, 20), (40, 70)]), fill_paint(wall));
+    ///# }
+    /// ```
+    pub fn pattern<I: Into<Arc<Image>>, P: Into<Coord>>(image: I, offset: P, scale: f32) -> Self {
+        Paint::ImagePattern {
+            image: image.into(),
+            offset: offset.into(),
+            scale,
+        }
+    }
+
+    /// Tile `image`, see [Paint::ImagePattern]
+    pub fn indexed_pattern<P: Into<Coord>>(image: &IndexedImage, offset: P, scale: f32) -> Self {
+        Paint::pattern(Image::from_indexed(image), offset, scale)
+    }
+
+    /// Dither `background` and `foreground`, see [Paint::Dither]
+    ///
+    /// # Usage
+    /// ```
+    ///# use buffer_graphics_lib::prelude::*;
+    ///# fn doc(graphics: &mut Graphics) {
+    /// let shade = Paint::dither(DitherPattern::Bayer4, 0.25, BLACK, WHITE);
+    /// graphics.draw_circle(Circle::new((30, 30), 20), fill_paint(shade));
+    ///# }
+    /// ```
+    pub fn dither(
+        pattern: DitherPattern,
+        density: f32,
+        background: Color,
+        foreground: Color,
+    ) -> Self {
+        Paint::Dither {
+            pattern,
+            density,
+            background,
+            foreground,
+        }
+    }
+
+    /// Color at `x`, `y` (in shape coordinates, or screen coordinates for [Paint::Dither])
+    pub fn color_at(&self, x: f32, y: f32) -> Color {
+        match self {
+            Paint::LinearGradient {
+                start,
+                end,
+                gradient,
+            } => {
+                let (dx, dy) = ((end.x - start.x) as f32, (end.y - start.y) as f32);
+                let len_sq = dx * dx + dy * dy;
+                if len_sq == 0.0 {
+                    return gradient.color_at(0.0);
+                }
+                let position = ((x - start.x as f32) * dx + (y - start.y as f32) * dy) / len_sq;
+                gradient.color_at(position)
+            }
+            Paint::RadialGradient {
+                center,
+                radius,
+                gradient,
+            } => {
+                let distance = (x - center.x as f32).hypot(y - center.y as f32);
+                if *radius == 0 {
+                    return gradient.color_at(if distance == 0.0 { 0.0 } else { 1.0 });
+                }
+                gradient.color_at(distance / *radius as f32)
+            }
+            Paint::ImagePattern {
+                image,
+                offset,
+                scale,
+            } => {
+                if image.width() == 0 || image.height() == 0 || *scale <= 0.0 {
+                    return TRANSPARENT;
+                }
+                let x = ((x - offset.x as f32) / scale).floor() as isize;
+                let y = ((y - offset.y as f32) / scale).floor() as isize;
+                image.get_pixel(
+                    x.rem_euclid(image.width() as isize) as usize,
+                    y.rem_euclid(image.height() as isize) as usize,
+                )
+            }
+            Paint::Dither {
+                pattern,
+                density,
+                background,
+                foreground,
+            } => {
+                if pattern.is_foreground(x.floor() as isize, y.floor() as isize, *density) {
+                    *foreground
+                } else {
+                    *background
+                }
+            }
+        }
+    }
+
+    /// Color used when a single color is needed, i.e. the color at the start of the paint
+    pub(crate) fn first_color(&self) -> Color {
+        match self {
+            Paint::LinearGradient { gradient, .. } | Paint::RadialGradient { gradient, .. } => {
+                gradient.color_at(0.0)
+            }
+            Paint::ImagePattern { image, .. } => {
+                image.pixels().first().copied().unwrap_or(TRANSPARENT)
+            }
+            Paint::Dither { background, .. } => *background,
+        }
+    }
+}
+
+/// Color of each pixel when drawing a shape
+pub(crate) enum PixelColor<'a> {
+    Solid(Color),
+    Paint {
+        paint: &'a Paint,
+        /// Converts pixels back to shape coordinates, None if the transform is the identity
+        inverse: Option<Transform>,
+        /// Converts pixels to screen coordinates
+        translate: Coord,
+    },
+}
+
+impl PixelColor<'_> {
+    /// Color for `draw_type` when drawn with `transform` and `translate`
+    pub(crate) fn new<'a>(
+        draw_type: &'a DrawType,
+        transform: &Transform,
+        translate: Coord,
+    ) -> PixelColor<'a> {
+        match draw_type {
+            DrawType::FillPaint(paint) => {
+                let inverse = if transform.is_identity() {
+                    None
+                } else {
+                    transform.inverse()
+                };
+                PixelColor::Paint {
+                    paint,
+                    inverse,
+                    translate,
+                }
+            }
+            _ => PixelColor::Solid(draw_type.color()),
+        }
+    }
+
+    /// Color of the pixel at `x`, `y` (drawing coords)
+    #[inline]
+    pub(crate) fn at(&self, x: isize, y: isize) -> Color {
+        match self {
+            PixelColor::Solid(color) => *color,
+            PixelColor::Paint {
+                paint: paint @ Paint::Dither { .. },
+                translate,
+                ..
+            } => paint.color_at((x + translate.x) as f32, (y + translate.y) as f32),
+            PixelColor::Paint {
+                paint,
+                inverse: None,
+                ..
+            } => paint.color_at(x as f32, y as f32),
+            PixelColor::Paint {
+                paint,
+                inverse: Some(inverse),
+                ..
+            } => {
+                let (x, y) = inverse.apply_f32((x as f32, y as f32));
+                paint.color_at(x, y)
+            }
+        }
+    }
+}
+
+impl Graphics<'_> {
+    /// Fill `shape` a row at a time using `color` for each pixel, this ignores the transform
+    ///
+    /// Returns false (without drawing anything) if the shape can't be split into rows
+    pub(crate) fn fill_shape_with_paint(&mut self, shape: &ShapeBox, color: &PixelColor) -> bool {
+        crate::shapes::spans::for_each_span(shape, |y, start_x, end_x| {
+            self.fill_span_with(y, start_x, end_x, color)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::make_image;
+    use crate::prelude::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn gradient_stops() {
+        let gradient = Gradient::new(&[(1.0, WHITE), (0.0, BLACK), (0.5, RED)]);
+        assert_eq!(gradient.color_at(-1.0), BLACK);
+        assert_eq!(gradient.color_at(0.25), Color::new(128, 0, 0, 255));
+        assert_eq!(gradient.color_at(0.5), RED);
+        assert_eq!(gradient.color_at(2.0), WHITE);
+
+        let gradient = Gradient::between(BLACK, WHITE).with_repeat(GradientRepeat::Repeat);
+        assert_eq!(gradient.color_at(1.25), gradient.color_at(0.25));
+        let gradient = gradient.with_repeat(GradientRepeat::Reflect);
+        assert_eq!(gradient.color_at(1.25), gradient.color_at(0.75));
+        assert_eq!(gradient.color_at(-0.25), gradient.color_at(0.25));
+    }
+
+    #[test]
+    fn fills_shapes() {
+        let linear = Paint::linear((0, 0), (10, 0), Gradient::between(BLACK, WHITE));
+        let image = make_image(11, 11, |g| {
+            g.draw_rect(Rect::new((0, 0), (10, 4)), fill_paint(linear.clone()));
+            g.draw_triangle(
+                Triangle::new((0, 6), (10, 6), (0, 10)),
+                fill_paint(linear.clone()),
+            );
+        })
+        .unwrap();
+        assert_eq!(image.get_pixel(0, 2), BLACK);
+        assert_eq!(image.get_pixel(5, 2), Color::gray(128));
+        assert_eq!(image.get_pixel(10, 4), WHITE);
+        assert_eq!(image.get_pixel(1, 7), image.get_pixel(1, 0));
+        assert_eq!(image.get_pixel(10, 10), TRANSPARENT);
+
+        let radial = Paint::radial((5, 5), 4, Gradient::between(RED, BLUE));
+        let circle = make_image(11, 11, |g| {
+            g.draw_circle(Circle::new((5, 5), 4), fill_paint(radial))
+        })
+        .unwrap();
+        assert_eq!(circle.get_pixel(5, 5), RED);
+        assert_eq!(circle.get_pixel(1, 5), BLUE);
+        assert_eq!(circle.get_pixel(0, 0), TRANSPARENT);
+    }
+
+    #[test]
+    fn follows_transform() {
+        let paint = Paint::linear((0, 0), (4, 0), Gradient::between(BLACK, WHITE));
+        let image = make_image(10, 10, |g| {
+            g.set_transform(Transform::translation(5.0, 0.0));
+            g.draw_rect(Rect::new((0, 0), (4, 4)), fill_paint(paint.clone()));
+            g.set_anti_aliasing(true);
+            g.set_transform(Transform::IDENTITY);
+            g.set_translate(coord!(0, 5));
+            g.draw_rect(Rect::new((0, 0), (4, 4)), fill_paint(paint.clone()));
+        })
+        .unwrap();
+        assert_eq!(image.get_pixel(5, 0), BLACK);
+        assert_eq!(image.get_pixel(9, 0), WHITE);
+        assert_eq!(image.get_pixel(0, 5), BLACK);
+        assert_eq!(image.get_pixel(4, 9), WHITE);
+    }
+
+    #[test]
+    fn image_pattern() {
+        let tile = Image::new(vec![RED, BLUE, GREEN, WHITE], 2, 2).unwrap();
+        let paint = Paint::pattern(tile.clone(), (1, 0), 2.0);
+        assert_eq!(paint.color_at(1.0, 0.0), RED);
+        assert_eq!(paint.color_at(2.0, 1.0), RED);
+        assert_eq!(paint.color_at(3.0, 0.0), BLUE);
+        assert_eq!(paint.color_at(0.0, 2.0), WHITE);
+        assert_eq!(paint.color_at(5.0, 4.0), RED);
+
+        let mut shapes = ShapeCollection::default();
+        InsertShape::insert_above(
+            &mut shapes,
+            Rect::new((0, 0), (3, 1)),
+            fill_paint(Paint::pattern(tile, (0, 0), 1.0)),
+        );
+        let image = make_image(4, 3, |g| g.draw(&shapes)).unwrap();
+        assert_eq!(
+            image.pixels()[..8],
+            [RED, BLUE, RED, BLUE, GREEN, WHITE, GREEN, WHITE]
+        );
+        assert_eq!(image.get_pixel(0, 2), TRANSPARENT);
+    }
+
+    #[test]
+    fn image_pattern_is_shared_and_transformed() {
+        let tile = Image::new(vec![RED, BLUE, GREEN, WHITE], 2, 2).unwrap();
+        let draw_type = fill_paint(Paint::pattern(tile, (0, 0), 1.0));
+        let copy = draw_type.clone();
+        match (&draw_type, &copy) {
+            (
+                DrawType::FillPaint(Paint::ImagePattern { image, .. }),
+                DrawType::FillPaint(Paint::ImagePattern { image: copied, .. }),
+            ) => assert!(Arc::ptr_eq(image, copied)),
+            _ => panic!("not a pattern"),
+        }
+
+        let image = make_image(6, 5, |g| {
+            g.set_transform(Transform::translation(2.0, 1.0));
+            g.draw_polygon(
+                Polygon::new(&[(0, 0), (3, 0), (3, 3), (0, 3)]),
+                copy.clone(),
+            );
+        })
+        .unwrap();
+        assert_eq!(image.get_pixel(0, 0), TRANSPARENT);
+        assert_eq!(image.get_pixel(2, 1), RED);
+        assert_eq!(image.get_pixel(3, 1), BLUE);
+        assert_eq!(image.get_pixel(2, 2), GREEN);
+        assert_eq!(image.get_pixel(5, 4), WHITE);
+    }
+
+    #[test]
+    fn paint_fills_are_clipped() {
+        let paint = fill_paint(Paint::linear(
+            (0, 0),
+            (5, 0),
+            Gradient::between(BLACK, WHITE),
+        ));
+        let draw = |clip: &dyn Fn(&mut Clip)| {
+            make_image(6, 4, |g| {
+                g.set_translate(coord!(1, 0));
+                clip(g.clip_mut());
+                g.draw_rect(Rect::new((0, 0), (4, 3)), paint.clone());
+            })
+            .unwrap()
+        };
+        let full = draw(&|_| {});
+        assert_eq!(full.get_pixel(0, 0), TRANSPARENT);
+        assert_eq!(full.get_pixel(1, 0), BLACK);
+
+        let rect = draw(&|clip| clip.set_valid_rect(Rect::new((2, 1), (3, 2))));
+        let pixels = draw(&|clip| clip.custom((0..24).map(|i| i % 2 == 0).collect()));
+        for y in 0..4 {
+            for x in 0..6 {
+                let expected = if (2..=3).contains(&x) && (1..=2).contains(&y) {
+                    full.get_pixel(x, y)
+                } else {
+                    TRANSPARENT
+                };
+                assert_eq!(rect.get_pixel(x, y), expected, "{x},{y}");
+                let expected = if (x + y * 6) % 2 == 0 {
+                    full.get_pixel(x, y)
+                } else {
+                    TRANSPARENT
+                };
+                assert_eq!(pixels.get_pixel(x, y), expected, "{x},{y}");
+            }
+        }
+    }
+
+    #[test]
+    fn dither() {
+        let bayer4: Vec<usize> = (0..16).map(|i| super::bayer(i % 4, i / 4, 4)).collect();
+        assert_eq!(
+            bayer4,
+            [0, 8, 2, 10, 12, 4, 14, 6, 3, 11, 1, 9, 15, 7, 13, 5]
+        );
+
+        for pattern in [
+            DitherPattern::Bayer2,
+            DitherPattern::Bayer4,
+            DitherPattern::Bayer8,
+        ] {
+            let count = |density: f32| {
+                (0..64)
+                    .filter(|i| pattern.is_foreground(i % 8, i / 8, density))
+                    .count()
+            };
+            assert_eq!(count(0.0), 0);
+            assert_eq!(count(0.5), 32);
+            assert_eq!(count(1.0), 64);
+        }
+
+        let paint = Paint::dither(DitherPattern::Checkerboard, 0.0, BLACK, WHITE);
+        let image = make_image(4, 2, |g| {
+            g.draw_rect(Rect::new((0, 0), (1, 1)), fill_paint(paint.clone()));
+            g.set_translate(coord!(1, 0));
+            g.set_transform(Transform::translation(1.0, 0.0));
+            g.draw_rect(Rect::new((0, 0), (1, 1)), fill_paint(paint.clone()));
+        })
+        .unwrap();
+        assert_eq!(
+            image.pixels(),
+            [WHITE, BLACK, WHITE, BLACK, BLACK, WHITE, BLACK, WHITE]
+        );
+
+        let hatch = DitherPattern::DiagonalHatch(3);
+        assert!(hatch.is_foreground(0, 0, 0.0));
+        assert!(hatch.is_foreground(2, 1, 0.0));
+        assert!(!hatch.is_foreground(1, 1, 0.0));
+    }
+}
@@ -0,0 +1,184 @@
+#[cfg(feature = "rayon")]
+use crate::drawing::Renderable;
+use crate::Graphics;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+impl Graphics<'_> {
+    /// Split this graphics into `count` horizontal bands that don't overlap
+    ///
+    /// Each band is a [Graphics] covering some of the rows of this buffer, they use the same
+    /// coordinates as this graphics (see [origin](Graphics::origin)) and copy its translate,
+    /// transform, clip, custom font and modes, so drawing the same commands to every band
+    /// produces the same result as drawing them to this graphics. Pixels outside a band's rows
+    /// are ignored.
+    ///
+    /// The bands can be sent to different threads, for example
+    /// ```
+    ///# use buffer_graphics_lib::prelude::*;
+    ///# fn doc(graphics: &mut Graphics) {
+    /// std::thread::scope(|scope| {
+    ///     for mut band in graphics.split_bands(4) {
+    ///         scope.spawn(move || band.draw_circle(Circle::new((100, 100), 50), fill(RED)));
+    ///     }
+    /// });
+    ///# }
+    /// ```
+    ///
+    /// If `count` is more than the number of rows then there will be one band per row
+    ///
+    /// [Custom](crate::GraphicsBuffer::Custom) buffers can't be split and always return one band
+    pub fn split_bands(&mut self, count: usize) -> Vec<Graphics<'_>> {
+        let count = count.clamp(1, self.height.max(1));
+        if let Some(dirty) = &mut self.dirty {
+            dirty.mark_region(0, 0, self.width, self.height);
+        }
+        let rows_per_band = self.height / count;
+        let extra_rows = self.height % count;
+        let row_len = self.index(0, 1);
+        let len = self.buffer.len();
+        let mut remaining = Some(self.buffer.slice_mut(0, len));
+        let mut bands = Vec::with_capacity(count);
+        let mut top = 0;
+        for i in 0..count {
+            let Some(unused) = remaining.take() else {
+                break;
+            };
+            let mut height = rows_per_band + usize::from(i < extra_rows);
+            let buffer = if i + 1 == count {
+                unused
+            } else {
+                let mid = (row_len * height).min(unused.len());
+                match unused.split_at(mid) {
+                    Ok((buffer, rest)) => {
+                        remaining = Some(rest);
+                        buffer
+                    }
+                    Err(unused) => {
+                        height = self.height - top;
+                        unused
+                    }
+                }
+            };
+            bands.push(Graphics {
+                buffer,
+                width: self.width,
+                height,
+                stride: self.stride,
+                origin: self.origin + (0, top as isize),
+                translate: self.translate,
+                transform: self.transform,
+                clip: self.clip.clone(),
+                blend_mode: self.blend_mode,
+                composite_mode: self.composite_mode,
+                anti_aliasing: self.anti_aliasing,
+                custom_font: self.custom_font.clone(),
+                state_stack: vec![],
+                dirty: None,
+                index_method: self.index_method,
+                clear_method: self.clear_method,
+            });
+            top += height;
+        }
+        bands
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl Graphics<'_> {
+    /// Split into one band per thread (see [split_bands](Graphics::split_bands)) and run `method`
+    /// on every band in parallel
+    pub fn par_draw<F: Fn(&mut Graphics) + Sync>(&mut self, method: F) {
+        let count = rayon::current_num_threads();
+        self.split_bands(count)
+            .into_par_iter()
+            .for_each(|mut band| method(&mut band));
+    }
+
+    /// Render every item in `renderables` (in order) to every band in parallel
+    ///
+    /// See [par_draw](Graphics::par_draw)
+    pub fn par_render<T>(&mut self, renderables: &[&(dyn Renderable<T> + Sync)]) {
+        self.par_draw(|band| {
+            for renderable in renderables {
+                renderable.render(band);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::*;
+
+    fn scene(graphics: &mut Graphics) {
+        graphics.clear_aware(BLACK);
+        graphics.draw_circle(Circle::new((10, 12), 7), fill(RED));
+        graphics.draw_line((0, 0), (19, 23), GREEN);
+        graphics.draw_text("Hi", TextPos::px(coord!(2, 14)), WHITE);
+    }
+
+    #[test]
+    fn bands_match_single_threaded() {
+        let mut expected = Graphics::create_buffer_u8(20, 24);
+        scene(&mut Graphics::new_u8_rgba(&mut expected, 20, 24).unwrap());
+
+        for count in [1, 3, 5, 7, 24, 100] {
+            let mut buffer = Graphics::create_buffer_u8(20, 24);
+            let mut graphics = Graphics::new_u8_rgba(&mut buffer, 20, 24).unwrap();
+            let bands = graphics.split_bands(count);
+            assert_eq!(bands.len(), count.min(24));
+            assert_eq!(bands.iter().map(|b| b.height()).sum::<usize>(), 24);
+            std::thread::scope(|scope| {
+                for mut band in bands {
+                    scope.spawn(move || scene(&mut band));
+                }
+            });
+            assert_eq!(buffer, expected, "{count} bands");
+        }
+    }
+
+    #[test]
+    fn bands_of_viewport() {
+        let mut buffer = Graphics::create_buffer_u32(6, 6);
+        let mut graphics = Graphics::new_u32_rgba(&mut buffer, 6, 6).unwrap();
+        let mut view = graphics.viewport((1, 1), 4, 4).unwrap();
+        for mut band in view.split_bands(3) {
+            band.clear(RED);
+            assert_eq!(band.get_pixel(0, band.origin().y, false), Some(RED));
+        }
+        let image = graphics.copy_to_image();
+        for y in 0..6 {
+            for x in 0..6 {
+                let expected = if (1..=4).contains(&x) && (1..=4).contains(&y) {
+                    RED
+                } else {
+                    TRANSPARENT
+                };
+                assert_eq!(image.get_pixel(x, y), expected, "{x},{y}");
+            }
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_render_matches_single_threaded() {
+        let mut expected = Graphics::create_buffer_u8(20, 24);
+        scene(&mut Graphics::new_u8_rgba(&mut expected, 20, 24).unwrap());
+
+        let mut buffer = Graphics::create_buffer_u8(20, 24);
+        let mut graphics = Graphics::new_u8_rgba(&mut buffer, 20, 24).unwrap();
+        graphics.par_draw(scene);
+        assert_eq!(buffer, expected);
+
+        let text = Text::new("Par", TextPos::px(coord!(1, 1)), RED);
+        let mut buffer = Graphics::create_buffer_u8(20, 24);
+        let mut graphics = Graphics::new_u8_rgba(&mut buffer, 20, 24).unwrap();
+        graphics.par_render(&[&text]);
+        let mut expected = Graphics::create_buffer_u8(20, 24);
+        Graphics::new_u8_rgba(&mut expected, 20, 24)
+            .unwrap()
+            .draw(&text);
+        assert_eq!(buffer, expected);
+    }
+}
@@ -0,0 +1,302 @@
+use crate::blending::BlendMode;
+use ici_files::prelude::*;
+
+/// A buffer in a pixel format not supported by [GraphicsBuffer](crate::GraphicsBuffer), for
+/// example 1-bit e-ink displays or planar VGA memory
+///
+/// Use with [Graphics::new_custom](crate::Graphics::new_custom), the [Graphics] handles
+/// bounds, clipping, translate, etc so `x` and `y` are always inside the buffer
+///
+/// # Usage
+/// ```
+///# use buffer_graphics_lib::prelude::*;
+/// struct OneBit {
+///     bits: Vec<u8>,
+/// }
+///
+/// impl PixelFormat for OneBit {
+///     fn bytes_per_pixel(&self) -> usize {
+///         0
+///     }
+///
+///     fn stride(&self) -> usize {
+///         8
+///     }
+///
+///     fn byte_len(&self) -> Option<usize> {
+///         Some(self.bits.len())
+///     }
+///
+///     fn read(&self, x: usize, y: usize) -> Color {
+///         if self.bits[y] & (1 << x) == 0 { BLACK } else { WHITE }
+///     }
+///
+///     fn write(&mut self, x: usize, y: usize, color: Color) {
+///         if color_to_gray(color) >= 128 {
+///             self.bits[y] |= 1 << x;
+///         } else {
+///             self.bits[y] &= !(1 << x);
+///         }
+///     }
+/// }
+///
+/// let mut format = OneBit { bits: vec![0; 8] };
+/// let mut graphics = Graphics::new_custom(&mut format, 8, 8).unwrap();
+/// graphics.draw_line((0, 0), (7, 0), WHITE);
+/// assert_eq!(graphics.get_pixel(3, 0, false), Some(WHITE));
+/// ```
+///
+/// [Graphics]: crate::Graphics
+pub trait PixelFormat {
+    /// Size of each pixel in bytes, 0 if pixels are smaller than a byte
+    fn bytes_per_pixel(&self) -> usize;
+
+    /// Number of pixels from the start of one row to the start of the next, this must be
+    /// at least the width of the [Graphics](crate::Graphics)
+    fn stride(&self) -> usize;
+
+    /// Size of the buffer in bytes, if known
+    ///
+    /// [Graphics::new_custom](crate::Graphics::new_custom) uses this with
+    /// [bytes_per_pixel](PixelFormat::bytes_per_pixel) and [stride](PixelFormat::stride) to
+    /// check the buffer is big enough, pixels smaller than a byte are counted as one bit
+    fn byte_len(&self) -> Option<usize> {
+        None
+    }
+
+    /// Get the color of the pixel at `x`, `y`
+    fn read(&self, x: usize, y: usize) -> Color;
+
+    /// Replace the pixel at `x`, `y` with `color`
+    fn write(&mut self, x: usize, y: usize, color: Color);
+
+    /// Combine `color` with the pixel at `x`, `y` using `blend_mode`
+    ///
+    /// This is only called for colors that aren't opaque or when the blend mode isn't
+    /// [BlendMode::Normal], by default the pixel is read, blended and written
+    fn blend(&mut self, x: usize, y: usize, color: Color, blend_mode: BlendMode) {
+        let existing = self.read(x, y);
+        self.write(x, y, blend_mode.blend(existing, color));
+    }
+}
+
+/// Part of a [PixelFormat] buffer, pixels are addressed by `x + y * stride` from the start
+/// of the buffer, i.e. every pixel is one element
+pub struct CustomBuffer<'a> {
+    format: &'a mut (dyn PixelFormat + Send + 'a),
+    stride: usize,
+    offset: usize,
+    len: usize,
+}
+
+impl<'a> CustomBuffer<'a> {
+    pub(crate) fn new(format: &'a mut (dyn PixelFormat + Send + 'a), height: usize) -> Self {
+        let stride = format.stride();
+        Self {
+            format,
+            stride,
+            offset: 0,
+            len: stride * height,
+        }
+    }
+}
+
+impl CustomBuffer<'_> {
+    #[inline]
+    fn coord(&self, idx: usize) -> (usize, usize) {
+        let idx = self.offset + idx;
+        (idx % self.stride, idx / self.stride)
+    }
+
+    pub(crate) fn get_color(&self, idx: usize) -> Color {
+        let (x, y) = self.coord(idx);
+        self.format.read(x, y)
+    }
+
+    pub(crate) fn set_color(&mut self, idx: usize, color: Color) {
+        let (x, y) = self.coord(idx);
+        self.format.write(x, y, color);
+    }
+
+    pub(crate) fn blend_color(&mut self, idx: usize, color: Color, blend_mode: BlendMode) {
+        let (x, y) = self.coord(idx);
+        self.format.blend(x, y, color, blend_mode);
+    }
+
+    /// Number of pixels (including any padding at the end of rows)
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn slice_mut(&mut self, start: usize, end: usize) -> CustomBuffer<'_> {
+        CustomBuffer {
+            stride: self.stride,
+            offset: self.offset + start,
+            len: end - start,
+            format: &mut *self.format,
+        }
+    }
+}
+
+/// Convert `color` to RGB565 (5 bits red, 6 bits green, 5 bits blue), alpha is discarded
+#[inline]
+pub fn color_to_rgb565(color: Color) -> u16 {
+    ((color.r as u16 >> 3) << 11) | ((color.g as u16 >> 2) << 5) | (color.b as u16 >> 3)
+}
+
+/// Convert RGB565 `value` to an opaque color, the low bits are filled so that white stays white
+#[inline]
+pub fn rgb565_to_color(value: u16) -> Color {
+    let r = ((value >> 11) & 0x1F) as u8;
+    let g = ((value >> 5) & 0x3F) as u8;
+    let b = (value & 0x1F) as u8;
+    Color::new(
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+        255,
+    )
+}
+
+/// Convert `color` to its luminance (using the BT.601 weights), alpha is discarded
+#[inline]
+pub fn color_to_gray(color: Color) -> u8 {
+    ((color.r as u32 * 77 + color.g as u32 * 150 + color.b as u32 * 29 + 128) >> 8) as u8
+}
+
+/// Convert luminance `value` to an opaque gray color
+#[inline]
+pub fn gray_to_color(value: u8) -> Color {
+    Color::new(value, value, value, 255)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rgb565_round_trip() {
+        for color in [BLACK, WHITE, RED, GREEN, BLUE] {
+            assert_eq!(rgb565_to_color(color_to_rgb565(color)), color);
+        }
+        assert_eq!(color_to_rgb565(Color::new(255, 0, 0, 0)), 0xF800);
+        assert_eq!(color_to_rgb565(Color::new(0, 255, 0, 255)), 0x07E0);
+        assert_eq!(
+            rgb565_to_color(color_to_rgb565(Color::new(100, 150, 200, 255))),
+            Color::new(99, 150, 206, 255)
+        );
+    }
+
+    #[test]
+    fn gray_conversion() {
+        assert_eq!(color_to_gray(WHITE), 255);
+        assert_eq!(color_to_gray(BLACK), 0);
+        assert_eq!(color_to_gray(RED), 77);
+        assert_eq!(gray_to_color(10), Color::new(10, 10, 10, 255));
+    }
+
+    fn scene(graphics: &mut crate::Graphics) {
+        graphics.clear(BLUE);
+        graphics.draw_rect(
+            crate::prelude::Rect::new((1, 1), (2, 2)),
+            crate::prelude::fill(RED),
+        );
+        graphics.set_pixel(0, 3, WHITE.with_alpha(128));
+    }
+
+    #[test]
+    fn all_formats_draw() {
+        let expected = crate::make_image(4, 4, scene).unwrap();
+        let opaque = |convert: fn(Color) -> Color| -> Vec<Color> {
+            expected.pixels().iter().map(|c| convert(*c)).collect()
+        };
+
+        let mut buffer = crate::Graphics::create_buffer_u8(4, 4);
+        let mut graphics = crate::Graphics::new_u8_bgra(&mut buffer, 4, 4).unwrap();
+        scene(&mut graphics);
+        assert_eq!(graphics.copy_to_image().pixels(), expected.pixels());
+        assert_eq!(&buffer[0..4], &[255, 0, 0, 255]);
+
+        let mut buffer = crate::Graphics::create_buffer_u8_rgb(4, 4);
+        let mut graphics = crate::Graphics::new_u8_rgb(&mut buffer, 4, 4).unwrap();
+        scene(&mut graphics);
+        assert_eq!(
+            graphics.copy_to_image().pixels(),
+            opaque(|c| c.with_alpha(255))
+        );
+        assert_eq!(&buffer[15..18], &[255, 0, 0]);
+
+        let mut buffer = crate::Graphics::create_buffer_u16(4, 4);
+        let mut graphics = crate::Graphics::new_u16_rgb565(&mut buffer, 4, 4).unwrap();
+        scene(&mut graphics);
+        assert_eq!(
+            graphics.copy_to_image().pixels(),
+            opaque(|c| rgb565_to_color(color_to_rgb565(c)))
+        );
+        assert_eq!(buffer[5], 0xF800);
+
+        let mut buffer = crate::Graphics::create_buffer_u8_gray(4, 4);
+        let mut graphics = crate::Graphics::new_u8_gray(&mut buffer, 4, 4).unwrap();
+        scene(&mut graphics);
+        assert_eq!(
+            graphics.copy_to_image().pixels(),
+            opaque(|c| gray_to_color(color_to_gray(c)))
+        );
+        assert_eq!(buffer[5], 77);
+
+        assert!(crate::Graphics::new_u8_rgb(&mut [0; 47], 4, 4).is_err());
+    }
+
+    struct Grid<'a> {
+        pixels: &'a mut Vec<Color>,
+        stride: usize,
+    }
+
+    impl PixelFormat for Grid<'_> {
+        fn bytes_per_pixel(&self) -> usize {
+            4
+        }
+
+        fn stride(&self) -> usize {
+            self.stride
+        }
+
+        fn byte_len(&self) -> Option<usize> {
+            Some(self.pixels.len() * 4)
+        }
+
+        fn read(&self, x: usize, y: usize) -> Color {
+            self.pixels[x + y * self.stride]
+        }
+
+        fn write(&mut self, x: usize, y: usize, color: Color) {
+            self.pixels[x + y * self.stride] = color;
+        }
+    }
+
+    #[test]
+    fn custom_format() {
+        let expected = crate::make_image(4, 4, |g| {
+            scene(g);
+            g.viewport((2, 0), 2, 2).unwrap().clear(GREEN);
+        })
+        .unwrap();
+        let mut pixels = vec![BLACK; 6 * 4];
+        let mut grid = Grid {
+            pixels: &mut pixels,
+            stride: 6,
+        };
+        let mut graphics = crate::Graphics::new_custom(&mut grid, 4, 4).unwrap();
+        scene(&mut graphics);
+        graphics.viewport((2, 0), 2, 2).unwrap().clear(GREEN);
+        assert_eq!(graphics.split_bands(3).len(), 1);
+        assert_eq!(graphics.copy_to_image().pixels(), expected.pixels());
+        assert!(crate::Graphics::new_custom(&mut grid, 7, 4).is_err());
+        assert!(matches!(
+            crate::Graphics::new_custom(&mut grid, 4, 5),
+            Err(crate::GraphicsError::InvalidBufferLength(112, 96))
+        ));
+        assert_eq!(pixels[4], BLACK, "padding is untouched");
+    }
+}
@@ -0,0 +1,236 @@
+use crate::image::Image;
+use crate::Graphics;
+use graphics_shapes::coord::Coord;
+use graphics_shapes::rect::Rect;
+use graphics_shapes::Shape;
+use ici_files::prelude::*;
+
+/// Restrict a copy of `len` pixels from `src` to `dest` so both are inside `0..size`
+///
+/// Returns the new `src`, `dest` and `len` or None if nothing is inside
+fn clip_axis(src: isize, dest: isize, len: isize, size: usize) -> Option<(usize, usize, usize)> {
+    let shift = 0.max(-src).max(-dest);
+    let (src, dest) = (src + shift, dest + shift);
+    let len = (len - shift)
+        .min(size as isize - src)
+        .min(size as isize - dest);
+    if len <= 0 {
+        None
+    } else {
+        Some((src as usize, dest as usize, len as usize))
+    }
+}
+
+impl Graphics<'_> {
+    /// Copy the pixels inside `src` so that the top left of `src` is at `dest`, the regions can overlap
+    ///
+    /// Both `src` and `dest` are in buffer coordinates (see [origin](Graphics::origin)), i.e. the
+    /// translate, transform, clip and modes are ignored and pixels are copied exactly.
+    /// Any part of either region outside of the buffer is skipped
+    ///
+    /// # Usage
+    /// ```
+    ///# use buffer_graphics_lib::prelude::*;
+    ///# fn doc(graphics: &mut Graphics) {
+    /// // duplicate a 16x16 tile
+    /// graphics.copy_region(Rect::new((0, 0), (15, 15)), (16, 0));
+    ///# }
+    /// ```
+    pub fn copy_region<P: Into<Coord>>(&mut self, src: Rect, dest: P) {
+        let src_xy = Coord::new(src.left(), src.top()) - self.origin;
+        let dest = dest.into() - self.origin;
+        let width = src.width() as isize + 1;
+        let height = src.height() as isize + 1;
+        let Some((src_x, dest_x, width)) = clip_axis(src_xy.x, dest.x, width, self.width) else {
+            return;
+        };
+        let Some((src_y, dest_y, height)) = clip_axis(src_xy.y, dest.y, height, self.height) else {
+            return;
+        };
+        if let Some(dirty) = &mut self.dirty {
+            dirty.mark_region(dest_x, dest_y, width, height);
+        }
+        let row_len = self.index(width, 0);
+        for row in 0..height {
+            // copy from the bottom when moving down so rows aren't overwritten before they're read
+            let row = if dest_y > src_y {
+                height - 1 - row
+            } else {
+                row
+            };
+            let start = self.index(src_x, src_y + row);
+            self.buffer
+                .copy_within(start, start + row_len, self.index(dest_x, dest_y + row));
+        }
+    }
+
+    /// Move every pixel by `dx`, `dy`, pixels moved outside the buffer are lost and the
+    /// uncovered pixels are set to `fill`
+    ///
+    /// Like [clear](Graphics::clear) this ignores translate, transform, clip and modes
+    ///
+    /// # Usage
+    /// ```
+    ///# use buffer_graphics_lib::prelude::*;
+    ///# fn doc(graphics: &mut Graphics) {
+    /// // move text up by one line
+    /// graphics.scroll(0, -(PixelFont::Standard6x7.line_height() as isize), BLACK);
+    ///# }
+    /// ```
+    pub fn scroll(&mut self, dx: isize, dy: isize, fill: Color) {
+        let (width, height) = (self.width as isize, self.height as isize);
+        if width == 0 || height == 0 {
+            return;
+        }
+        self.copy_region(
+            Rect::new(self.origin, self.origin + (width - 1, height - 1)),
+            self.origin + (dx, dy),
+        );
+        let dx = dx.clamp(-width, width);
+        let dy = dy.clamp(-height, height);
+        let rows = if dy >= 0 { 0..dy } else { height + dy..height };
+        for y in rows.clone() {
+            self.fill_row(y as usize, 0, self.width, fill);
+        }
+        let columns = if dx >= 0 { 0..dx } else { width + dx..width };
+        if !columns.is_empty() {
+            for y in (0..height).filter(|y| !rows.contains(y)) {
+                self.fill_row(y as usize, columns.start as usize, columns.len(), fill);
+            }
+        }
+    }
+
+    /// Copy the pixels inside `rect` to an image, the alpha is kept (if the buffer format has it)
+    ///
+    /// If `use_translate` is true then `rect` will be moved by `self.translate`, the transform
+    /// and clip are ignored. Any part of `rect` outside of the buffer is transparent in the image
+    ///
+    /// # Usage
+    /// ```
+    ///# use buffer_graphics_lib::prelude::*;
+    ///# fn doc(graphics: &mut Graphics) {
+    /// // magnify the area around the cursor
+    /// let area = graphics.copy_region_to_image(Rect::new((40, 40), (49, 49)), false);
+    /// graphics.draw_image((0, 0), &area.scale(Scaling::nearest_neighbour(4, 4).unwrap()));
+    ///# }
+    /// ```
+    pub fn copy_region_to_image(&self, rect: Rect, use_translate: bool) -> Image {
+        let width = rect.width() + 1;
+        let height = rect.height() + 1;
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in rect.top()..=rect.bottom() {
+            for x in rect.left()..=rect.right() {
+                pixels.push(self.get_pixel(x, y, use_translate).unwrap_or(TRANSPARENT));
+            }
+        }
+        Image::new(pixels, width, height).expect(
+            "Copy region to image failed, please create GitHub issue for buffer-graphics-lib",
+        )
+    }
+
+    /// Set `len` pixels from `x`, `y` (local) to `color`, ignoring translate, clip and modes
+    fn fill_row(&mut self, y: usize, x: usize, len: usize, color: Color) {
+        if let Some(dirty) = &mut self.dirty {
+            dirty.mark_region(x, y, len, 1);
+        }
+        let start = self.index(x, y);
+        let end = self.index(x + len, y);
+        (self.clear_method)(&mut self.buffer.slice_mut(start, end), color);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::*;
+    use crate::GraphicsBuffer;
+
+    fn numbered(width: usize, height: usize) -> Image {
+        let pixels = (0..width * height)
+            .map(|i| Color::new(i as u8, 0, 0, 255))
+            .collect();
+        Image::new(pixels, width, height).unwrap()
+    }
+
+    fn red(graphics: &Graphics, x: usize, y: usize) -> u8 {
+        graphics.get_pixel(x as isize, y as isize, false).unwrap().r
+    }
+
+    #[test]
+    fn copy_overlapping() {
+        let mut buffer = Graphics::create_buffer_u8(6, 6);
+        let mut graphics = Graphics::new_u8_rgba(&mut buffer, 6, 6).unwrap();
+        graphics.set_translate(coord!(3, 3));
+        let image = numbered(6, 6);
+
+        graphics.draw_image((-3, -3), &image);
+        graphics.copy_region(Rect::new((0, 0), (2, 2)), (1, 1));
+        assert_eq!(red(&graphics, 0, 0), 0);
+        assert_eq!(red(&graphics, 1, 1), 0);
+        assert_eq!(red(&graphics, 2, 1), 1);
+        assert_eq!(red(&graphics, 3, 3), 14);
+        assert_eq!(red(&graphics, 4, 3), 22);
+
+        graphics.draw_image((-3, -3), &image);
+        graphics.copy_region(Rect::new((2, 2), (5, 5)), (1, 1));
+        assert_eq!(red(&graphics, 0, 0), 0);
+        assert_eq!(red(&graphics, 1, 1), 14);
+        assert_eq!(red(&graphics, 4, 4), 35);
+        assert_eq!(red(&graphics, 5, 5), 35);
+
+        graphics.draw_image((-3, -3), &image);
+        graphics.copy_region(Rect::new((-2, 4), (1, 10)), (2, 0));
+        assert_eq!(red(&graphics, 4, 0), 24);
+        assert_eq!(red(&graphics, 5, 1), 31);
+        assert_eq!(red(&graphics, 4, 2), 16);
+        assert_eq!(red(&graphics, 3, 0), 3);
+    }
+
+    #[test]
+    fn scroll() {
+        let mut buffer = Graphics::create_buffer_u32(4, 3);
+        let mut padded = vec![0; 5 * 3];
+        for mut graphics in [
+            Graphics::new_u32_rgba(&mut buffer, 4, 3).unwrap(),
+            Graphics::new_with_stride(GraphicsBuffer::RgbaU32(&mut padded), 4, 3, 5).unwrap(),
+        ] {
+            graphics.draw_image((0, 0), &numbered(4, 3));
+            graphics.track_dirty_regions(1);
+            graphics.scroll(1, -1, BLUE);
+            for (i, value) in [4, 5, 6, 8, 9, 10].iter().enumerate() {
+                assert_eq!(red(&graphics, 1 + i % 3, i / 3), *value);
+            }
+            assert_eq!(graphics.get_pixel(0, 0, false), Some(BLUE));
+            assert_eq!(graphics.get_pixel(3, 2, false), Some(BLUE));
+            assert_eq!(
+                graphics.take_dirty_regions(),
+                vec![Rect::new((0, 0), (3, 2))]
+            );
+            graphics.scroll(-10, 0, RED);
+            assert_eq!(graphics.get_pixel(3, 1, false), Some(RED));
+            assert_eq!(graphics.get_pixel(0, 1, false), Some(RED));
+        }
+        assert_eq!(padded[4], 0, "padding is untouched");
+    }
+
+    #[test]
+    fn copy_region_to_image() {
+        let mut buffer = Graphics::create_buffer_u8(5, 5);
+        let mut graphics = Graphics::new_u8_rgba(&mut buffer, 5, 5).unwrap();
+        graphics.draw_image((0, 0), &numbered(5, 5));
+        graphics.set_composite_mode(CompositeMode::Source);
+        graphics.set_pixel(1, 2, RED.with_alpha(100));
+        graphics.set_translate(coord!(1, 1));
+
+        let image = graphics.copy_region_to_image(Rect::new((1, 1), (2, 3)), false);
+        assert_eq!(image.width(), 2);
+        assert_eq!(image.height(), 3);
+        assert_eq!(image.get_pixel(0, 0).r, 6);
+        assert_eq!(image.get_pixel(1, 2).r, 17);
+        assert_eq!(image.get_pixel(0, 1), RED.with_alpha(100));
+
+        let image = graphics.copy_region_to_image(Rect::new((2, 2), (4, 4)), true);
+        assert_eq!(image.get_pixel(0, 0).r, 18);
+        assert_eq!(image.get_pixel(1, 1).r, 24);
+        assert_eq!(image.get_pixel(2, 2), TRANSPARENT);
+    }
+}
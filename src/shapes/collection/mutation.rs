@@ -5,7 +5,7 @@ impl ShapeCollection {
     pub fn with_draw_type(&self, draw_type: DrawType) -> ShapeCollection {
         let shapes: Vec<Drawable<ShapeBox>> = self
             .iter()
-            .map(|shape| shape.with_draw_type(draw_type))
+            .map(|shape| shape.with_draw_type(draw_type.clone()))
             .collect();
         ShapeCollection {
             shapes,
@@ -1,19 +1,152 @@
 pub mod collection;
 pub mod polyline;
+pub(crate) mod spans;
 
 use crate::drawable::{DrawType, Drawable};
 use crate::drawing::Renderable;
+use crate::paint::PixelColor;
+use crate::transform::TransformedShape;
 use crate::Graphics;
+use fnv::FnvHashSet;
 use graphics_shapes::prelude::*;
 use graphics_shapes::shape_box::ShapeBox;
 
 impl<S: Shape + Clone> Renderable<S> for Drawable<S> {
     fn render(&self, graphics: &mut Graphics) {
-        let color = self.draw_type().color();
+        render(
+            graphics,
+            self.obj(),
+            self.draw_type(),
+            Some(self.drawing_points().as_slice()),
+        );
+    }
+}
+
+/// Draw `shape` with `draw_type`, see [render_shape] for `points`
+pub(crate) fn render<S: Shape + Clone>(
+    graphics: &mut Graphics,
+    shape: &S,
+    draw_type: &DrawType,
+    points: Option<&[Coord]>,
+) {
+    match draw_type {
+        DrawType::FillAndStroke(fill, stroke) => {
+            render_shape(graphics, shape, &DrawType::Fill(*fill), None);
+            render_shape(graphics, shape, &DrawType::Stroke(*stroke), points);
+        }
+        draw_type => render_shape(graphics, shape, draw_type, points),
+    }
+}
+
+/// Draw `shape` with `draw_type` (which must not be [DrawType::FillAndStroke])
+///
+/// `points` are the pixels for `draw_type` from [CreateDrawable::from_obj], they're only
+/// calculated (if None) when the shape can't be drawn any other way
+fn render_shape<S: Shape + Clone>(
+    graphics: &mut Graphics,
+    shape: &S,
+    draw_type: &DrawType,
+    points: Option<&[Coord]>,
+) {
+    let color = draw_type.color();
+    if let DrawType::StrokeWith(color, style) = draw_type {
+        if !style.is_thin() {
+            graphics.stroke_shape(&shape.to_shape_box(), *color, *style);
+            return;
+        }
+    }
+
+    let transform = graphics.get_transform();
+    let pixel_color = PixelColor::new(draw_type, &transform, graphics.get_translate());
+    if graphics.anti_aliasing() {
+        let shape = if transform.is_identity() {
+            shape.to_shape_box()
+        } else {
+            match TransformedShape::new(&shape.to_shape_box(), &transform) {
+                TransformedShape::Line(start, end) => Line::new(start, end).to_shape_box(),
+                TransformedShape::Polygon(points) => Polygon::new(&points).to_shape_box(),
+            }
+        };
+        if graphics.draw_anti_aliased(&shape, draw_type) {
+            return;
+        }
+    }
+    if !transform.is_identity() {
+        match TransformedShape::new(&shape.to_shape_box(), &transform) {
+            TransformedShape::Line(start, end) => graphics.plot_line(start, end, color),
+            TransformedShape::Polygon(points) => {
+                let polygon = Polygon::new(&points);
+                if draw_type.is_stroke() {
+                    let pixels: FnvHashSet<Coord> = polygon.outline_pixels().into_iter().collect();
+                    for px in pixels {
+                        graphics.set_pixel(px.x, px.y, pixel_color.at(px.x, px.y));
+                    }
+                } else {
+                    fill_with_outline(graphics, &polygon, &pixel_color);
+                }
+            }
+        }
+        return;
+    }
 
-        for px in self.drawing_points() {
-            graphics.set_pixel(px.x, px.y, color);
+    let filled = match draw_type {
+        DrawType::Fill(color) => graphics.fill_shape(&shape.to_shape_box(), *color),
+        DrawType::FillPaint(_) => {
+            graphics.fill_shape_with_paint(&shape.to_shape_box(), &pixel_color)
         }
+        _ => false,
+    };
+    if filled {
+        return;
+    }
+
+    let calculated;
+    let points = match points {
+        Some(points) => points,
+        None => {
+            calculated = if draw_type.is_stroke() {
+                shape.outline_pixels()
+            } else {
+                shape.filled_pixels()
+            };
+            &calculated
+        }
+    };
+    for px in points {
+        graphics.set_pixel(px.x, px.y, pixel_color.at(px.x, px.y));
+    }
+}
+
+/// Fill `polygon` (already transformed) and its outline a row at a time, each pixel is only
+/// drawn once
+fn fill_with_outline(graphics: &mut Graphics, polygon: &Polygon, color: &PixelColor) {
+    let (_, top, _, bottom) = graphics.visible_bounds();
+    let mut spans = vec![];
+    let mut add = |y: isize, start_x: isize, end_x: isize| {
+        if y >= top && y <= bottom {
+            spans.push((y, start_x, end_x));
+        }
+    };
+    spans::for_each_span(&polygon.to_shape_box(), &mut add);
+    for px in polygon.outline_pixels() {
+        add(px.y, px.x, px.x);
+    }
+    spans.sort_unstable();
+    let mut current: Option<(isize, isize, isize)> = None;
+    for (y, start_x, end_x) in spans {
+        match &mut current {
+            Some((row, _, row_end)) if *row == y && start_x <= *row_end + 1 => {
+                *row_end = (*row_end).max(end_x)
+            }
+            _ => {
+                if let Some((y, start_x, end_x)) = current.replace((y, start_x, end_x)) {
+                    graphics.fill_span_with(y, start_x, end_x, color);
+                }
+            }
+        }
+    }
+    if let Some((y, start_x, end_x)) = current {
+        graphics.fill_span_with(y, start_x, end_x, color);
     }
 }
 
@@ -32,10 +165,10 @@ macro_rules! create_drawable_from_points {
     ($shape: ty) => {
         impl CreateDrawable<$shape> for Drawable<$shape> {
             fn from_obj(shape: $shape, draw_type: DrawType) -> Drawable<$shape> {
-                let drawing_points = if draw_type.is_stroke() {
-                    shape.outline_pixels()
-                } else {
-                    shape.filled_pixels()
+                // the fill of FillAndStroke is drawn a row at a time so only the outline is stored
+                let drawing_points = match draw_type {
+                    DrawType::Fill(_) | DrawType::FillPaint(_) => shape.filled_pixels(),
+                    _ => shape.outline_pixels(),
                 };
                 Drawable::new(shape, draw_type, drawing_points)
             }
@@ -49,3 +182,84 @@ create_drawable_from_points!(Polygon);
 create_drawable_from_points!(Circle);
 create_drawable_from_points!(Ellipse);
 create_drawable_from_points!(ShapeBox);
+
+#[cfg(test)]
+mod test {
+    use crate::make_image;
+    use crate::prelude::*;
+    use graphics_shapes::shape_box::ShapeBox;
+
+    #[test]
+    fn fill_then_stroke() {
+        let rect = Rect::new((1, 1), (4, 3));
+        let drawable = Drawable::from_obj(rect.clone(), fill_and_stroke(RED, BLUE));
+        assert_eq!(drawable.drawing_points(), &rect.outline_pixels());
+
+        let separate = make_image(6, 5, |g| {
+            g.draw_rect(rect.clone(), fill(RED));
+            g.draw_rect(rect.clone(), stroke(BLUE));
+        })
+        .unwrap();
+        let combined = make_image(6, 5, |g| g.draw(&drawable)).unwrap();
+        assert_eq!(combined, separate);
+        assert_eq!(combined.get_pixel(2, 2), RED);
+        assert_eq!(combined.get_pixel(1, 1), BLUE);
+
+        let mut collection = ShapeCollection::default();
+        InsertShape::insert_above(
+            &mut collection,
+            ShapeBox::from(rect.clone()),
+            fill_and_stroke(RED, BLUE),
+        );
+        let moved = Transform::translation(1.0, 1.0);
+        let separate = make_image(6, 5, |g| {
+            g.set_transform(moved);
+            g.draw_rect(rect.clone(), fill(RED));
+            g.draw_rect(rect.clone(), stroke(BLUE));
+        })
+        .unwrap();
+        let transformed = make_image(6, 5, |g| {
+            g.set_transform(moved);
+            g.draw(&collection);
+        })
+        .unwrap();
+        assert_eq!(transformed, separate);
+    }
+
+    #[test]
+    fn transformed_fills_match_pixels() {
+        use crate::transform::TransformedShape;
+
+        let transform = Transform::rotation_around(30.0, (8, 8)).then(Transform::scale(1.5, 1.0));
+        let shapes = [
+            Rect::new((3, 3), (12, 9)).to_shape_box(),
+            Circle::new((8, 8), 6).to_shape_box(),
+            Triangle::new((1, 2), (15, 6), (5, 14)).to_shape_box(),
+            Polygon::new(&[(2, 2), (14, 3), (9, 8), (13, 14), (3, 12)]).to_shape_box(),
+        ];
+        let color = RED.with_alpha(100);
+        for shape in shapes {
+            let image = make_image(24, 18, |g| {
+                g.set_transform(transform);
+                g.draw(&Drawable::from_obj(shape.clone(), fill(color)));
+            })
+            .unwrap();
+            let polygon = match TransformedShape::new(&shape, &transform) {
+                TransformedShape::Polygon(points) => Polygon::new(&points),
+                TransformedShape::Line(..) => unreachable!(),
+            };
+            let expected = make_image(24, 18, |g| {
+                let pixels: fnv::FnvHashSet<Coord> = polygon
+                    .filled_pixels()
+                    .into_iter()
+                    .chain(polygon.outline_pixels())
+                    .collect();
+                for px in pixels {
+                    g.set_pixel(px.x, px.y, color);
+                }
+            })
+            .unwrap();
+            assert_eq!(image, expected, "{shape:?}");
+        }
+    }
+}
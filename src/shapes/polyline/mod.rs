@@ -4,6 +4,7 @@ pub mod rendering;
 use crate::shapes::polyline::error::PolylineError;
 use crate::shapes::polyline::error::PolylineError::{InvalidPolyline, PolylineAlreadyClosed};
 use crate::shapes::polyline::Segment::*;
+use crate::stroke::StrokeStyle;
 use graphics_shapes::coord::Coord;
 use ici_files::prelude::*;
 #[cfg(feature = "serde")]
@@ -20,6 +21,17 @@ pub enum Segment {
         angle_end: isize,
         radius: usize,
     },
+    /// Quadratic Bézier curve from the end of the previous segment to `end`
+    QuadTo {
+        control: Coord,
+        end: Coord,
+    },
+    /// Cubic Bézier curve from the end of the previous segment to `end`
+    CubicTo {
+        control1: Coord,
+        control2: Coord,
+        end: Coord,
+    },
 }
 
 impl Segment {
@@ -33,6 +45,8 @@ impl Segment {
                 angle_end,
                 ..
             } => Coord::from_angle(center, *radius, *angle_end),
+            QuadTo { end, .. } => *end,
+            CubicTo { end, .. } => *end,
         }
     }
 }
@@ -43,6 +57,8 @@ pub struct Polyline {
     segments: Vec<Segment>,
     color: Color,
     closed: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    style: StrokeStyle,
 }
 
 impl Polyline {
@@ -51,6 +67,7 @@ impl Polyline {
             segments,
             color,
             closed: false,
+            style: StrokeStyle::default(),
         }
     }
 
@@ -59,6 +76,7 @@ impl Polyline {
             segments: vec![Start(start_at.into())],
             color,
             closed: false,
+            style: StrokeStyle::default(),
         }
     }
 
@@ -99,6 +117,21 @@ impl Polyline {
         cloned.color = color;
         cloned
     }
+
+    /// Draw with `style`, wide or dashed polylines are drawn as a single stroke so the segments
+    /// are joined using [LineJoin](crate::stroke::LineJoin), the ends use
+    /// [LineCap](crate::stroke::LineCap) and the [Dash](crate::stroke::Dash) pattern continues
+    /// across segments
+    pub fn with_stroke_style(&self, style: StrokeStyle) -> Self {
+        let mut cloned = self.clone();
+        cloned.style = style;
+        cloned
+    }
+
+    #[inline]
+    pub fn stroke_style(&self) -> StrokeStyle {
+        self.style
+    }
 }
 
 impl Polyline {
@@ -129,6 +162,55 @@ impl Polyline {
         Ok(self)
     }
 
+    /// Add a quadratic Bézier curve from the end of the last segment to `end`, bending towards
+    /// `control`
+    ///
+    /// # Usage
+    /// ```
+    ///# use buffer_graphics_lib::prelude::*;
+    ///# use buffer_graphics_lib::shapes::polyline::error::PolylineError;
+    ///# fn doc(graphics: &mut Graphics) -> Result<(), PolylineError> {
+    /// let cable = Polyline::start((10, 10), WHITE)
+    ///     .add_quad_to((30, 40), (50, 10))?
+    ///     .add_cubic_to((60, -10), (80, 30), (90, 10))?;
+    /// graphics.draw(&cable);
+    ///# Ok(())
+    ///# }
+    /// ```
+    pub fn add_quad_to<P1: Into<Coord>, P2: Into<Coord>>(
+        mut self,
+        control: P1,
+        end: P2,
+    ) -> Result<Self, PolylineError> {
+        if self.closed {
+            return Err(PolylineAlreadyClosed);
+        }
+        self.segments.push(QuadTo {
+            control: control.into(),
+            end: end.into(),
+        });
+        Ok(self)
+    }
+
+    /// Add a cubic Bézier curve from the end of the last segment to `end`, leaving towards
+    /// `control1` and arriving from `control2`
+    pub fn add_cubic_to<P1: Into<Coord>, P2: Into<Coord>, P3: Into<Coord>>(
+        mut self,
+        control1: P1,
+        control2: P2,
+        end: P3,
+    ) -> Result<Self, PolylineError> {
+        if self.closed {
+            return Err(PolylineAlreadyClosed);
+        }
+        self.segments.push(CubicTo {
+            control1: control1.into(),
+            control2: control2.into(),
+            end: end.into(),
+        });
+        Ok(self)
+    }
+
     pub fn close(self) -> Result<Self, PolylineError> {
         if let Start(coord) = self.segments[0] {
             let mut tmp = self.add_line_to(coord)?;
@@ -169,4 +251,40 @@ mod test {
             ]
         )
     }
+
+    #[test]
+    fn curves() {
+        use crate::prelude::*;
+
+        let closed = Polyline::start((0, 0), RED)
+            .add_line_to((4, 0))
+            .unwrap()
+            .close()
+            .unwrap();
+        assert!(closed.clone().add_quad_to((1, 1), (2, 2)).is_err());
+        assert!(closed.add_cubic_to((1, 1), (2, 2), (3, 3)).is_err());
+
+        let quad = Polyline::start((0, 0), RED)
+            .add_quad_to((4, 8), (8, 0))
+            .unwrap();
+        assert_eq!(quad.segments[1].end_coord(), Coord::new(8, 0));
+        for style in [
+            StrokeStyle::new(1),
+            StrokeStyle::new(1).with_dash(Dash::new(100, 1)),
+        ] {
+            let image =
+                crate::make_image(9, 6, |g| g.draw(&quad.with_stroke_style(style))).unwrap();
+            assert_eq!(image.get_pixel(0, 0), RED);
+            assert_eq!(image.get_pixel(8, 0), RED);
+            assert_eq!(image.get_pixel(4, 4), RED);
+            assert_eq!(image.get_pixel(4, 5), TRANSPARENT);
+            assert_eq!(image.get_pixel(4, 3), TRANSPARENT);
+        }
+
+        let cubic = Polyline::start((0, 2), RED)
+            .add_cubic_to((2, 2), (5, 2), (7, 2))
+            .unwrap();
+        let line = crate::make_image(8, 4, |g| g.draw_line((0, 2), (7, 2), RED)).unwrap();
+        assert_eq!(crate::make_image(8, 4, |g| g.draw(&cubic)).unwrap(), line);
+    }
 }
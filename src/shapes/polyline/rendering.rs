@@ -1,8 +1,11 @@
+use crate::anti_aliasing::distance_to_segment;
 use crate::drawing::Renderable;
-use crate::shapes::polyline::Polyline;
 use crate::shapes::polyline::Segment::*;
+use crate::shapes::polyline::{Polyline, Segment};
 use crate::Graphics;
+use graphics_shapes::coord::Coord;
 use log::error;
+use std::mem::take;
 
 impl Renderable<Polyline> for Polyline {
     fn render(&self, graphics: &mut Graphics) {
@@ -17,6 +20,11 @@ impl Renderable<Polyline> for Polyline {
             return;
         };
 
+        if !self.style.is_thin() {
+            self.render_wide(graphics, last_coord);
+            return;
+        }
+
         for segment in self.segments.iter().skip(1) {
             match segment {
                 Start(_) => error!("Polyline is invalid, second start found"),
@@ -34,8 +42,125 @@ impl Renderable<Polyline> for Polyline {
                     false,
                     self.color,
                 ),
+                QuadTo { .. } | CubicTo { .. } => {
+                    let mut points = vec![];
+                    flatten_curve(&mut points, last_coord, segment);
+                    let mut start = last_coord;
+                    for (x, y) in points {
+                        let end = Coord::new(x.round() as isize, y.round() as isize);
+                        graphics.draw_line(start, end, self.color);
+                        start = end;
+                    }
+                }
+            }
+            last_coord = segment.end_coord();
+        }
+    }
+}
+
+impl Polyline {
+    /// Draw all the segments as one stroke using `self.style`
+    ///
+    /// This uses the same points as the 1px lines, so arcs are 1 degree steps and an arc that
+    /// doesn't start at the end of the last segment isn't connected to it
+    fn render_wide(&self, graphics: &mut Graphics, start: Coord) {
+        let point = |coord: Coord| (coord.x as f32, coord.y as f32);
+        let mut paths = vec![];
+        let mut current = vec![point(start)];
+        let mut last_coord = start;
+        for segment in self.segments.iter().skip(1) {
+            match segment {
+                Start(_) => error!("Polyline is invalid, second start found"),
+                LineTo(coord) => current.push(point(*coord)),
+                ArcAround {
+                    center,
+                    angle_start,
+                    angle_end,
+                    radius,
+                } => {
+                    if Coord::from_angle(center, *radius, *angle_start) != last_coord {
+                        paths.push(take(&mut current));
+                    }
+                    current.extend(
+                        (*angle_start..=*angle_end)
+                            .map(|degrees| point(Coord::from_angle(center, *radius, degrees))),
+                    );
+                }
+                QuadTo { .. } | CubicTo { .. } => flatten_curve(&mut current, last_coord, segment),
             }
             last_coord = segment.end_coord();
         }
+        let closed = self.closed && paths.is_empty();
+        if self.closed && !paths.is_empty() {
+            // the closing line ends at the start, so the last path continues into the first
+            current.extend(paths.remove(0));
+        }
+        paths.push(current);
+        let transform = graphics.get_transform();
+        for path in &mut paths {
+            for point in path.iter_mut() {
+                *point = transform.apply_f32(*point);
+            }
+        }
+        graphics.stroke_paths(&paths, closed, self.color, self.style);
+    }
+}
+
+/// Maximum distance (in pixels) between a curve and the lines it's drawn with
+const FLATNESS: f32 = 0.25;
+/// Maximum number of times a curve is split in half
+const MAX_DEPTH: usize = 10;
+
+/// Add points along `segment` (a curve starting at `start`) to `points`, `start` isn't added
+///
+/// Curves are split in half until each part is within [FLATNESS] of a straight line, so
+/// tight bends get more points than gentle ones
+fn flatten_curve(points: &mut Vec<(f32, f32)>, start: Coord, segment: &Segment) {
+    let point = |coord: &Coord| (coord.x as f32, coord.y as f32);
+    match segment {
+        QuadTo { control, end } => {
+            // a quadratic curve is a cubic curve with both controls 2/3 of the way to the control
+            let (start, control, end) = (point(&start), point(control), point(end));
+            let towards = |from: (f32, f32)| {
+                (
+                    from.0 + (control.0 - from.0) * 2.0 / 3.0,
+                    from.1 + (control.1 - from.1) * 2.0 / 3.0,
+                )
+            };
+            flatten_cubic(points, [start, towards(start), towards(end), end], 0);
+        }
+        CubicTo {
+            control1,
+            control2,
+            end,
+        } => flatten_cubic(
+            points,
+            [point(&start), point(control1), point(control2), point(end)],
+            0,
+        ),
+        _ => {}
+    }
+}
+
+fn flatten_cubic(points: &mut Vec<(f32, f32)>, curve: [(f32, f32); 4], depth: usize) {
+    let [start, control1, control2, end] = curve;
+    // measuring to the segment (not the infinite line) means controls past the ends of the
+    // curve aren't treated as flat
+    let flat = distance_to_segment(control1, start, end)
+        .max(distance_to_segment(control2, start, end))
+        <= FLATNESS;
+    if flat || depth >= MAX_DEPTH {
+        points.push(end);
+        return;
     }
+    let mid = |a: (f32, f32), b: (f32, f32)| ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+    let (ab, bc, cd) = (
+        mid(start, control1),
+        mid(control1, control2),
+        mid(control2, end),
+    );
+    let (abc, bcd) = (mid(ab, bc), mid(bc, cd));
+    let middle = mid(abc, bcd);
+    flatten_cubic(points, [start, ab, abc, middle], depth + 1);
+    flatten_cubic(points, [middle, bcd, cd, end], depth + 1);
 }
@@ -0,0 +1,201 @@
+use graphics_shapes::prelude::*;
+use graphics_shapes::shape_box::ShapeBox;
+
+/// Call `method` with `y, start_x, end_x` (inclusive) for each horizontal run of pixels covered
+/// by `shape` when filled, the runs cover the same pixels as [Shape::filled_pixels]
+///
+/// Returns false (without calling `method`) if the shape can't be split into runs, i.e. lines
+/// and rotated ellipses
+pub(crate) fn for_each_span<F: FnMut(isize, isize, isize)>(shape: &ShapeBox, method: F) -> bool {
+    match shape {
+        ShapeBox::Line(_) => return false,
+        ShapeBox::Ellipse(ellipse) if ellipse.angle() != 0 => return false,
+        ShapeBox::Rect(rect) => rect_spans(rect, method),
+        ShapeBox::Circle(circle) => circle_spans(circle, method),
+        ShapeBox::Ellipse(ellipse) => ellipse_spans(ellipse, method),
+        ShapeBox::Triangle(triangle) => triangle_spans(triangle, method),
+        ShapeBox::Polygon(polygon) => polygon_spans(polygon, method),
+    }
+    true
+}
+
+fn rect_spans<F: FnMut(isize, isize, isize)>(rect: &Rect, mut method: F) {
+    for y in rect.top()..=rect.bottom() {
+        method(y, rect.left(), rect.right());
+    }
+}
+
+fn circle_spans<F: FnMut(isize, isize, isize)>(circle: &Circle, mut method: F) {
+    let center = circle.center();
+    let radius = circle.radius() as isize;
+    let squared_radius = radius * radius;
+    for y in 0..radius {
+        let half_width = (((squared_radius - y * y) as f64).sqrt().round() as isize).max(0);
+        let (left, right) = (center.x - half_width, center.x + half_width);
+        method(center.y - y, left, right);
+        if y != 0 {
+            method(center.y + y, left, right);
+        }
+    }
+}
+
+fn ellipse_spans<F: FnMut(isize, isize, isize)>(ellipse: &Ellipse, mut method: F) {
+    let center = ellipse.center();
+    let height = ellipse.height() as isize / 2;
+    let width = ellipse.width() as isize / 2;
+    let height_sq = height * height;
+    let width_sq = width * width;
+    let limit = height_sq * width_sq;
+    let inside = |x: isize, y_amount: isize| x * x * height_sq + y_amount <= limit;
+    for y in -height..height {
+        let y_amount = y * y * width_sq;
+        let mut half_width = if height_sq == 0 {
+            width
+        } else {
+            (((limit - y_amount) / height_sq) as f64).sqrt() as isize
+        };
+        while half_width > 0 && !inside(half_width, y_amount) {
+            half_width -= 1;
+        }
+        while half_width < width && inside(half_width + 1, y_amount) {
+            half_width += 1;
+        }
+        if width > 0 && inside(half_width, y_amount) {
+            method(
+                center.y + y,
+                center.x - half_width,
+                center.x + half_width.min(width - 1),
+            );
+        }
+    }
+}
+
+fn triangle_spans<F: FnMut(isize, isize, isize)>(triangle: &Triangle, mut method: F) {
+    let mut sorted_points = triangle.points();
+    sorted_points.sort_by_key(|c| c.y);
+    let points = [0, 1, 2].map(|i| (sorted_points[i].x as f32, sorted_points[i].y as f32));
+    if points[1].1 == points[2].1 {
+        flat_bottom_spans(points, &mut method);
+    } else if points[0].1 == points[1].1 {
+        flat_top_spans(points, &mut method);
+    } else {
+        let split = (
+            points[0].0
+                + ((points[1].1 - points[0].1) / (points[2].1 - points[0].1))
+                    * (points[2].0 - points[0].0),
+            points[1].1,
+        );
+        flat_bottom_spans([points[0], points[1], split], &mut method);
+        flat_top_spans([points[1], split, points[2]], &mut method);
+    }
+}
+
+fn flat_bottom_spans<F: FnMut(isize, isize, isize)>(points: [(f32, f32); 3], method: &mut F) {
+    let slope1 = (points[1].0 - points[0].0) / (points[1].1 - points[0].1);
+    let slope2 = (points[2].0 - points[0].0) / (points[2].1 - points[0].1);
+    let mut x1 = points[0].0;
+    let mut x2 = points[0].0;
+    for y in (points[0].1 as isize)..(points[1].1 as isize) {
+        method(y, x1.min(x2) as isize, x1.max(x2) as isize + 1);
+        x1 += slope1;
+        x2 += slope2;
+    }
+}
+
+fn flat_top_spans<F: FnMut(isize, isize, isize)>(points: [(f32, f32); 3], method: &mut F) {
+    let slope1 = (points[2].0 - points[0].0) / (points[2].1 - points[0].1);
+    let slope2 = (points[2].0 - points[1].0) / (points[2].1 - points[1].1);
+    let mut x1 = points[2].0;
+    let mut x2 = points[2].0;
+    for y in ((points[0].1 as isize)..(points[2].1 as isize)).rev() {
+        method(y, x1.min(x2) as isize, x1.max(x2) as isize + 1);
+        x1 -= slope1;
+        x2 -= slope2;
+    }
+}
+
+fn polygon_spans<F: FnMut(isize, isize, isize)>(polygon: &Polygon, mut method: F) {
+    let poly = polygon.fpoints();
+    if poly.is_empty() {
+        return;
+    }
+    let mut nodes = vec![];
+    for y in polygon.top()..=polygon.bottom() {
+        nodes.clear();
+        let fy = y as f32;
+        let mut j = poly.len() - 1;
+        for i in 0..poly.len() {
+            if poly[i].1 < fy && poly[j].1 >= fy || poly[j].1 < fy && poly[i].1 >= fy {
+                nodes.push(
+                    poly[i].0
+                        + (fy - poly[i].1) / (poly[j].1 - poly[i].1) * (poly[j].0 - poly[i].0),
+                );
+            }
+            j = i;
+        }
+        nodes.sort_by(|lhs, rhs| lhs.total_cmp(rhs));
+        for pair in nodes.chunks_exact(2) {
+            let (start, end) = (pair[0] as isize, pair[1] as isize);
+            if start < end {
+                method(y, start + 1, end);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fnv::FnvHashSet;
+
+    fn assert_matches_filled_pixels(shape: ShapeBox) {
+        let mut pixels = FnvHashSet::default();
+        assert!(for_each_span(&shape, |y, start, end| {
+            for x in start..=end {
+                assert!(pixels.insert(Coord::new(x, y)), "{x},{y} drawn twice");
+            }
+        }));
+        let expected: FnvHashSet<Coord> = shape.filled_pixels().into_iter().collect();
+        assert_eq!(pixels, expected, "{shape:?}");
+    }
+
+    #[test]
+    fn spans_match_filled_pixels() {
+        assert_matches_filled_pixels(Rect::new((-2, 3), (7, 9)).to_shape_box());
+        assert_matches_filled_pixels(Rect::new((4, 4), (4, 4)).to_shape_box());
+        for radius in [0, 1, 2, 5, 13] {
+            assert_matches_filled_pixels(Circle::new((10, -3), radius).to_shape_box());
+        }
+        for (width, height) in [(10, 6), (7, 13), (1, 1), (0, 4), (20, 2)] {
+            let ellipse = Ellipse::new((15, 12), width, height);
+            assert_matches_filled_pixels(ellipse.to_shape_box());
+        }
+        for points in [
+            [(0, 0), (10, 0), (5, 8)],
+            [(5, 0), (0, 8), (10, 8)],
+            [(3, 1), (17, 6), (1, 14)],
+            [(9, 2), (2, 5), (12, 12)],
+            [(4, 4), (4, 4), (4, 4)],
+        ] {
+            assert_matches_filled_pixels(
+                Triangle::new(points[0], points[1], points[2]).to_shape_box(),
+            );
+        }
+        assert_matches_filled_pixels(
+            Polygon::new(&[(-3, 0), (8, -2), (12, 9), (4, 3), (0, 11)]).to_shape_box(),
+        );
+        assert_matches_filled_pixels(Polygon::new(&[(0, 0), (6, 0), (6, 6)]).to_shape_box());
+    }
+
+    #[test]
+    fn unsupported_shapes() {
+        assert!(!for_each_span(
+            &Line::new((0, 0), (4, 4)).to_shape_box(),
+            |_, _, _| {}
+        ));
+        assert!(!for_each_span(
+            &Ellipse::new((10, 10), 8, 4).rotate(30).to_shape_box(),
+            |_, _, _| {}
+        ));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::blending::{BlendMode, CompositeMode};
+use crate::clipping::Clip;
+use crate::transform::Transform;
+use crate::{CustomLetter, Graphics};
+use fnv::FnvHashMap;
+use graphics_shapes::coord::Coord;
+use log::error;
+
+/// Snapshot of the [Graphics] settings saved by [Graphics::save]
+#[derive(Debug, Clone)]
+pub(crate) struct GraphicsState {
+    translate: Coord,
+    transform: Transform,
+    clip: Clip,
+    custom_font: FnvHashMap<u8, CustomLetter>,
+    blend_mode: BlendMode,
+    composite_mode: CompositeMode,
+    anti_aliasing: bool,
+}
+
+impl GraphicsState {
+    /// Default settings for a `width`x`height` buffer
+    pub(crate) fn new(width: usize, height: usize) -> Self {
+        Self {
+            translate: Coord::default(),
+            transform: Transform::IDENTITY,
+            clip: Clip::new(width, height),
+            custom_font: FnvHashMap::default(),
+            blend_mode: BlendMode::default(),
+            composite_mode: CompositeMode::default(),
+            anti_aliasing: false,
+        }
+    }
+
+    pub(crate) fn reset_clip(&mut self, width: usize, height: usize) {
+        self.clip = Clip::new(width, height);
+    }
+}
+
+impl Graphics<'_> {
+    /// Replace the current settings with `state`, returning the previous settings
+    pub(crate) fn swap_state(&mut self, state: GraphicsState) -> GraphicsState {
+        GraphicsState {
+            translate: std::mem::replace(&mut self.translate, state.translate),
+            transform: std::mem::replace(&mut self.transform, state.transform),
+            clip: std::mem::replace(&mut self.clip, state.clip),
+            custom_font: std::mem::replace(&mut self.custom_font, state.custom_font),
+            blend_mode: std::mem::replace(&mut self.blend_mode, state.blend_mode),
+            composite_mode: std::mem::replace(&mut self.composite_mode, state.composite_mode),
+            anti_aliasing: std::mem::replace(&mut self.anti_aliasing, state.anti_aliasing),
+        }
+    }
+
+    /// Push the current translate, transform, clip, custom font, blend mode, composite mode and
+    /// anti-aliasing setting onto the state stack
+    ///
+    /// Use [restore](Graphics::restore) to return to these settings
+    pub fn save(&mut self) {
+        self.state_stack.push(GraphicsState {
+            translate: self.translate,
+            transform: self.transform,
+            clip: self.clip.clone(),
+            custom_font: self.custom_font.clone(),
+            blend_mode: self.blend_mode,
+            composite_mode: self.composite_mode,
+            anti_aliasing: self.anti_aliasing,
+        });
+    }
+
+    /// Pop the last saved settings from the state stack and apply them
+    ///
+    /// Does nothing (other than log an error) if there are no saved states
+    pub fn restore(&mut self) {
+        if let Some(state) = self.state_stack.pop() {
+            self.swap_state(state);
+        } else {
+            error!("Graphics::restore called without matching save");
+        }
+    }
+
+    /// Number of states currently saved
+    #[inline]
+    pub fn saved_state_count(&self) -> usize {
+        self.state_stack.len()
+    }
+
+    /// Run `method`, any changes it makes to the translate, transform, clip, custom font, blend mode,
+    /// composite mode or anti-aliasing setting are reverted afterwards
+    pub fn with_state<F: Fn(&mut Graphics)>(&mut self, method: F) {
+        self.save();
+        method(self);
+        self.restore();
+    }
+
+    /// Restrict the clip to the pixels valid in both the current clip and `clip`
+    ///
+    /// Unlike [set_clip](Graphics::set_clip) this can only shrink the drawable area
+    pub fn intersect_clip(&mut self, clip: &Clip) {
+        self.clip = self.clip.intersect(clip);
+    }
+
+    /// Run `method` with the clip intersected with `clip`, the previous state is restored afterwards
+    ///
+    /// Nested calls further restrict the drawable area, so a widget can't draw outside of its parent
+    pub fn with_clip<F: Fn(&mut Graphics)>(&mut self, clip: &Clip, method: F) {
+        self.save();
+        self.intersect_clip(clip);
+        method(self);
+        self.restore();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::make_image;
+    use crate::prelude::*;
+
+    #[test]
+    fn save_restore() {
+        let mut buf = Graphics::create_buffer_u8(4, 4);
+        let mut graphics = Graphics::new_u8_rgba(&mut buf, 4, 4).unwrap();
+        graphics.save();
+        graphics.set_translate(coord!(1, 1));
+        graphics.set_blend_mode(BlendMode::Multiply);
+        graphics
+            .custom_font
+            .insert(chr_to_code('a'), CustomLetter::default());
+        graphics
+            .clip_mut()
+            .set_valid_rect(Rect::new((0, 0), (1, 1)));
+        graphics.restore();
+
+        assert_eq!(graphics.get_translate(), coord!(0, 0));
+        assert_eq!(graphics.blend_mode(), BlendMode::Normal);
+        assert!(graphics.custom_font.is_empty());
+        assert!(graphics.clip().is_nothing());
+        assert_eq!(graphics.saved_state_count(), 0);
+    }
+
+    #[test]
+    fn nested_clips_intersect() {
+        let mut outer = Clip::new(4, 4);
+        outer.set_valid_rect(Rect::new((0, 0), (2, 2)));
+        let mut inner = Clip::new(4, 4);
+        inner.set_valid_rect(Rect::new((1, 1), (3, 3)));
+
+        let image = make_image(4, 4, |g| {
+            g.with_clip(&outer, |g| {
+                g.with_clip(&inner, |g| g.clear_aware(RED));
+            });
+        })
+        .unwrap();
+
+        for y in 0..4 {
+            for x in 0..4 {
+                let expected = if (1..=2).contains(&x) && (1..=2).contains(&y) {
+                    RED
+                } else {
+                    TRANSPARENT
+                };
+                assert_eq!(image.get_pixel(x, y), expected, "{x},{y}");
+            }
+        }
+    }
+}
@@ -0,0 +1,971 @@
+use crate::transform::outline_points;
+use crate::Graphics;
+use graphics_shapes::prelude::*;
+use graphics_shapes::shape_box::ShapeBox;
+use ici_files::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Shape of the ends of open strokes (lines and polylines)
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum LineCap {
+    /// Ends at the end point
+    #[default]
+    Butt,
+    /// Extends past the end point by half the width as a semicircle
+    Round,
+    /// Extends past the end point by half the width
+    Square,
+}
+
+/// Shape of the corners where stroke segments meet
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum LineJoin {
+    /// Sharp corner, very sharp corners (more than 4 times the width) are beveled instead
+    #[default]
+    Miter,
+    Round,
+    /// Corner is cut off
+    Bevel,
+}
+
+/// How outlines are drawn, see [DrawType::StrokeWith](crate::drawable::DrawType::StrokeWith)
+///
+/// Strokes are centered on the outline (even widths have the extra pixel above/left of it) and
+/// the end points of lines are included like 1px lines, so [LineCap::Butt] extends past the end
+/// point by half a pixel. Wide and dashed strokes follow the transform (the width isn't scaled)
+/// but aren't anti-aliased
+///
+/// # Usage
+/// ```
+///# use buffer_graphics_lib::prelude::*;
+///# fn doc(graphics: &mut Graphics) {
+/// let style = StrokeStyle::new(4).with_cap(LineCap::Round).with_join(LineJoin::Round);
+/// graphics.draw_rect(Rect::new((10, 10), (50, 30)), stroke_with(RED, style));
+/// graphics.draw_line_with_style((10, 40), (50, 60), BLUE, style);
+///# }
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct StrokeStyle {
+    /// Width in pixels, 0 is treated as 1
+    pub width: usize,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    /// If set the outline is broken into dashes, see [Dash]
+    pub dash: Option<Dash>,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        StrokeStyle::new(1)
+    }
+}
+
+impl StrokeStyle {
+    pub fn new(width: usize) -> Self {
+        Self {
+            width,
+            cap: LineCap::default(),
+            join: LineJoin::default(),
+            dash: None,
+        }
+    }
+
+    pub fn with_cap(self, cap: LineCap) -> Self {
+        Self { cap, ..self }
+    }
+
+    pub fn with_join(self, join: LineJoin) -> Self {
+        Self { join, ..self }
+    }
+
+    pub fn with_dash(self, dash: Dash) -> Self {
+        Self {
+            dash: Some(dash),
+            ..self
+        }
+    }
+
+    /// True if this is drawn the same as a 1px [DrawType::Stroke](crate::drawable::DrawType::Stroke)
+    #[inline]
+    pub fn is_thin(&self) -> bool {
+        self.width <= 1 && !matches!(self.dash, Some(dash) if dash.off > 0)
+    }
+}
+
+/// Dash pattern for [StrokeStyle], lengths are in pixels along the outline
+///
+/// The pattern continues across the segments of a shape or [Polyline](crate::shapes::polyline::Polyline)
+/// (including arcs) and starts again for each shape. Each dash is capped using [StrokeStyle::cap]
+///
+/// # Usage
+/// ```
+///# use buffer_graphics_lib::prelude::*;
+///# fn doc(graphics: &mut Graphics, frame: usize) {
+/// // marching ants
+/// let style = StrokeStyle::new(1).with_dash(Dash::new(4, 4).with_offset(frame));
+/// graphics.draw_rect(Rect::new((10, 10), (50, 30)), stroke_with(WHITE, style));
+///# }
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Dash {
+    /// Length of each dash
+    pub on: usize,
+    /// Length of each gap, if this is 0 the outline is solid
+    pub off: usize,
+    /// How far into the pattern the outline starts, increasing this moves the dashes backwards
+    /// along the outline
+    pub offset: usize,
+}
+
+impl Dash {
+    pub fn new(on: usize, off: usize) -> Self {
+        Self { on, off, offset: 0 }
+    }
+
+    /// Alternating single pixels
+    pub fn dotted() -> Self {
+        Dash::new(1, 1)
+    }
+
+    pub fn with_offset(self, offset: usize) -> Self {
+        Self { offset, ..self }
+    }
+
+    #[inline]
+    fn period(&self) -> usize {
+        self.on + self.off
+    }
+
+    /// True if the pixel `distance` pixels along the outline is part of a dash
+    #[inline]
+    fn is_on(&self, distance: usize) -> bool {
+        self.off == 0 || (distance + self.offset) % self.period() < self.on
+    }
+}
+
+/// Offset pixels are sampled at, so that edges exactly between pixels only include one pixel
+const SAMPLE_OFFSET: f32 = 0.001;
+/// Maximum ratio of miter length to half the width
+const MITER_LIMIT: f32 = 4.0;
+
+type Point = (f32, f32);
+
+/// Pixels covered by a stroke, stored as spans per row which are merged before drawing so
+/// each pixel is only drawn once and transparent strokes don't have darker overlaps
+struct StrokePixels {
+    /// Spans (start x, end x inclusive) for each visible row, starting at `bounds.1`
+    rows: Vec<Vec<(isize, isize)>>,
+    /// Visible area in drawing coords (left, top, right, bottom)
+    bounds: (isize, isize, isize, isize),
+}
+
+impl StrokePixels {
+    fn new(bounds: (isize, isize, isize, isize)) -> Self {
+        let height = (bounds.3 - bounds.1 + 1).max(0) as usize;
+        Self {
+            rows: vec![vec![]; height],
+            bounds,
+        }
+    }
+
+    /// Add the pixels from `start_x` to `end_x` (inclusive) on row `y`, if they're visible
+    fn add_span(&mut self, y: isize, start_x: isize, end_x: isize) {
+        let (left, top, right, bottom) = self.bounds;
+        let (start_x, end_x) = (start_x.max(left), end_x.min(right));
+        if y >= top && y <= bottom && start_x <= end_x {
+            self.rows[(y - top) as usize].push((start_x, end_x));
+        }
+    }
+
+    /// Merge overlapping spans, returns the spans (y, start x, end x) to draw
+    fn into_spans(self) -> impl Iterator<Item = (isize, isize, isize)> {
+        let top = self.bounds.1;
+        self.rows
+            .into_iter()
+            .enumerate()
+            .flat_map(move |(i, mut spans)| {
+                spans.sort_unstable();
+                let mut merged: Vec<(isize, isize)> = Vec::with_capacity(spans.len());
+                for (start_x, end_x) in spans {
+                    match merged.last_mut() {
+                        Some(last) if start_x <= last.1 + 1 => last.1 = last.1.max(end_x),
+                        _ => merged.push((start_x, end_x)),
+                    }
+                }
+                let y = top + i as isize;
+                merged
+                    .into_iter()
+                    .map(move |(start_x, end_x)| (y, start_x, end_x))
+            })
+    }
+
+    /// Pixels around `points` that are inside the visible area
+    fn area(&self, points: &[Point], padding: f32) -> (isize, isize, isize, isize) {
+        let left = points.iter().map(|p| p.0).fold(f32::MAX, f32::min) - padding;
+        let right = points.iter().map(|p| p.0).fold(f32::MIN, f32::max) + padding;
+        let top = points.iter().map(|p| p.1).fold(f32::MAX, f32::min) - padding;
+        let bottom = points.iter().map(|p| p.1).fold(f32::MIN, f32::max) + padding;
+        (
+            (left.floor() as isize).max(self.bounds.0),
+            (top.floor() as isize).max(self.bounds.1),
+            (right.ceil() as isize).min(self.bounds.2),
+            (bottom.ceil() as isize).min(self.bounds.3),
+        )
+    }
+
+    /// Add the pixels on row `y` with a sample point from `start` to `end`
+    fn add_sampled_span(&mut self, y: isize, start: f32, end: f32) {
+        if start <= end {
+            let start_x = (start - SAMPLE_OFFSET).ceil() as isize;
+            let end_x = (end - SAMPLE_OFFSET).floor() as isize;
+            self.add_span(y, start_x, end_x);
+        }
+    }
+
+    /// Add pixels inside the convex polygon `points`
+    fn add_convex(&mut self, points: &[Point]) {
+        let (_, top, _, bottom) = self.area(points, 0.0);
+        let edges = || (0..points.len()).map(|i| (points[i], points[(i + 1) % points.len()]));
+        let area: f32 = edges().map(|(a, b)| a.0 * b.1 - b.0 * a.1).sum();
+        if area == 0.0 {
+            return;
+        }
+        let side = area.signum();
+        for y in top..=bottom {
+            let sample_y = y as f32 + SAMPLE_OFFSET;
+            let (mut start, mut end) = (f32::MIN, f32::MAX);
+            // the sample is inside if it's on the same side of every edge, each edge limits
+            // the row to one side of where it crosses
+            for (a, b) in edges() {
+                let cross_at_zero = side * ((b.0 - a.0) * (sample_y - a.1) + (b.1 - a.1) * a.0);
+                let slope = side * (b.1 - a.1);
+                if slope > 0.0 {
+                    end = end.min(cross_at_zero / slope);
+                } else if slope < 0.0 {
+                    start = start.max(cross_at_zero / slope);
+                } else if cross_at_zero < 0.0 {
+                    start = f32::MAX;
+                }
+            }
+            self.add_sampled_span(y, start, end);
+        }
+    }
+
+    /// Add pixels within `radius` of `center`
+    fn add_disc(&mut self, center: Point, radius: f32) {
+        let (_, top, _, bottom) = self.area(&[center], radius);
+        for y in top..=bottom {
+            let dy = y as f32 + SAMPLE_OFFSET - center.1;
+            if dy * dy <= radius * radius {
+                let dx = (radius * radius - dy * dy).sqrt();
+                self.add_sampled_span(y, center.0 - dx, center.0 + dx);
+            }
+        }
+    }
+
+    /// Add pixels where `distance` (distance from the outline) is within `half_width`
+    ///
+    /// `size` is the half width and half height of the outline, only pixels between ellipses
+    /// 1px outside and inside the edges of the stroke are checked
+    fn add_ring<F: Fn(f32, f32) -> f32>(
+        &mut self,
+        center: Coord,
+        size: (f32, f32),
+        half_width: f32,
+        distance: F,
+    ) {
+        /// Distance from the center to the edge of the ellipse `(a, b)` on the row `dy` from
+        /// the center, None if the row doesn't cross the ellipse
+        fn ellipse_x((a, b): (f32, f32), dy: f32) -> Option<f32> {
+            let t = 1.0 - (dy * dy) / (b * b);
+            (a > 0.0 && b > 0.0 && t >= 0.0).then(|| a * t.sqrt())
+        }
+
+        let outer = (size.0 + half_width + 1.0, size.1 + half_width + 1.0);
+        let inner = (size.0 - half_width - 1.0, size.1 - half_width - 1.0);
+        let (center_x, center_y) = (center.x as f32, center.y as f32);
+        let corners = [
+            (center_x - outer.0, center_y - outer.1),
+            (center_x + outer.0, center_y + outer.1),
+        ];
+        let (left, top, right, bottom) = self.area(&corners, 0.0);
+        for y in top..=bottom {
+            let sample_y = y as f32 + SAMPLE_OFFSET;
+            let dy = sample_y - center_y;
+            let Some(outer_x) = ellipse_x(outer, dy) else {
+                continue;
+            };
+            let start_x = ((center_x - outer_x).floor() as isize).max(left);
+            let end_x = ((center_x + outer_x).ceil() as isize).min(right);
+            let (gap_start, gap_end) = match ellipse_x(inner, dy) {
+                Some(inner_x) => (
+                    (center_x - inner_x - SAMPLE_OFFSET).ceil() as isize,
+                    (center_x + inner_x - SAMPLE_OFFSET).floor() as isize,
+                ),
+                None => (end_x + 1, end_x),
+            };
+            let mut run: Option<(isize, isize)> = None;
+            let columns =
+                (start_x..=end_x.min(gap_start - 1)).chain(gap_end.max(start_x - 1) + 1..=end_x);
+            for x in columns {
+                let distance = distance(x as f32 + SAMPLE_OFFSET, sample_y);
+                if distance < -half_width || distance >= half_width {
+                    continue;
+                }
+                match &mut run {
+                    Some((_, run_end)) if *run_end + 1 == x => *run_end = x,
+                    _ => {
+                        if let Some((run_start, run_end)) = run.replace((x, x)) {
+                            self.add_span(y, run_start, run_end);
+                        }
+                    }
+                }
+            }
+            if let Some((run_start, run_end)) = run {
+                self.add_span(y, run_start, run_end);
+            }
+        }
+    }
+
+    /// Add pixels covered by `points` stroked with `style` (including the dash pattern)
+    ///
+    /// Returns how far along the dash pattern the path ends (0 if it's not dashed)
+    fn add_styled_path(&mut self, points: &[Point], closed: bool, style: StrokeStyle) -> usize {
+        match style.dash.filter(|dash| dash.off > 0) {
+            None => {
+                self.add_path(points, closed, style, 0.5);
+                0
+            }
+            Some(dash) if style.width <= 1 => self.add_dashed_pixels(points, closed, dash),
+            Some(dash) => {
+                for dash in dash_path(points, closed, dash) {
+                    self.add_path(&dash, false, style, 0.0);
+                }
+                let length: f32 = points
+                    .windows(2)
+                    .map(|pair| (pair[1].0 - pair[0].0).hypot(pair[1].1 - pair[0].1))
+                    .sum();
+                // open paths are extended by half a pixel at each end, see dash_path
+                (length + 1.0).round() as usize
+            }
+        }
+    }
+
+    /// Add every pixel along `points` that is part of a dash, each pixel is 1 step along the pattern
+    ///
+    /// Returns the number of steps
+    fn add_dashed_pixels(&mut self, points: &[Point], closed: bool, dash: Dash) -> usize {
+        let mut coords: Vec<Coord> = points
+            .iter()
+            .map(|p| Coord::new(p.0.round() as isize, p.1.round() as isize))
+            .collect();
+        coords.dedup();
+        if closed && coords.len() > 1 && coords.first() != coords.last() {
+            coords.push(coords[0]);
+        }
+        let mut distance = 0;
+        let mut add = |px: Coord, stroke: &mut StrokePixels| {
+            if dash.is_on(distance) {
+                stroke.add_span(px.y, px.x, px.x);
+            }
+            distance += 1;
+        };
+        if let Some(first) = coords.first() {
+            add(*first, self);
+        }
+        let last_segment = coords.len().saturating_sub(2);
+        for (i, pair) in coords.windows(2).enumerate() {
+            let mut line = line_pixels(pair[0], pair[1]);
+            // the start of a closed outline has already been drawn
+            if closed && i == last_segment {
+                line.pop();
+            }
+            for px in line.into_iter().skip(1) {
+                add(px, self);
+            }
+        }
+        distance
+    }
+
+    /// Add pixels covered by `points` stroked with `style`
+    ///
+    /// `butt_extend` is how far [LineCap::Butt] extends past the ends of open paths
+    fn add_path(&mut self, points: &[Point], closed: bool, style: StrokeStyle, butt_extend: f32) {
+        let mut points: Vec<Point> = points.to_vec();
+        points.dedup();
+        if closed && points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        let half = style.width.max(1) as f32 / 2.0;
+        if points.is_empty() {
+            return;
+        }
+        if points.len() == 1 {
+            match style.cap {
+                LineCap::Round => self.add_disc(points[0], half),
+                _ => {
+                    let extend = if style.cap == LineCap::Square {
+                        half
+                    } else {
+                        butt_extend
+                    };
+                    let (x, y) = points[0];
+                    self.add_convex(&[
+                        (x - extend, y - half),
+                        (x + extend, y - half),
+                        (x + extend, y + half),
+                        (x - extend, y + half),
+                    ]);
+                }
+            }
+            return;
+        }
+        let segment_count = if closed {
+            points.len()
+        } else {
+            points.len() - 1
+        };
+        let direction = |i: usize| {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            let len = (b.0 - a.0).hypot(b.1 - a.1);
+            ((b.0 - a.0) / len, (b.1 - a.1) / len)
+        };
+        let cap_extend = match style.cap {
+            LineCap::Butt => butt_extend,
+            LineCap::Round => 0.0,
+            LineCap::Square => half.max(0.5),
+        };
+        for i in 0..segment_count {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            let (ux, uy) = direction(i);
+            let (nx, ny) = (-uy * half, ux * half);
+            let start = if !closed && i == 0 { cap_extend } else { 0.0 };
+            let end = if !closed && i + 1 == segment_count {
+                cap_extend
+            } else {
+                0.0
+            };
+            let (a, b) = (
+                (a.0 - ux * start, a.1 - uy * start),
+                (b.0 + ux * end, b.1 + uy * end),
+            );
+            self.add_convex(&[
+                (a.0 + nx, a.1 + ny),
+                (b.0 + nx, b.1 + ny),
+                (b.0 - nx, b.1 - ny),
+                (a.0 - nx, a.1 - ny),
+            ]);
+        }
+        if !closed && style.cap == LineCap::Round {
+            self.add_disc(points[0], half);
+            self.add_disc(points[points.len() - 1], half);
+        }
+        let joins = if closed {
+            0..points.len()
+        } else {
+            1..points.len() - 1
+        };
+        for i in joins {
+            let incoming = direction((i + points.len() - 1) % points.len());
+            let outgoing = direction(i);
+            self.add_join(points[i], incoming, outgoing, half, style.join);
+        }
+    }
+
+    /// Fill the gap on the outside of the corner at `point`
+    fn add_join(
+        &mut self,
+        point: Point,
+        incoming: Point,
+        outgoing: Point,
+        half: f32,
+        join: LineJoin,
+    ) {
+        if join == LineJoin::Round {
+            self.add_disc(point, half);
+            return;
+        }
+        let cross = incoming.0 * outgoing.1 - incoming.1 * outgoing.0;
+        if cross.abs() < f32::EPSILON {
+            return;
+        }
+        // normals on the outside of the corner
+        let side = -cross.signum();
+        let n1 = (-incoming.1 * side, incoming.0 * side);
+        let n2 = (-outgoing.1 * side, outgoing.0 * side);
+        let p1 = (point.0 + n1.0 * half, point.1 + n1.1 * half);
+        let p2 = (point.0 + n2.0 * half, point.1 + n2.1 * half);
+        let sum = (n1.0 + n2.0, n1.1 + n2.1);
+        let sum_sq = sum.0 * sum.0 + sum.1 * sum.1;
+        if join == LineJoin::Miter && sum_sq > 0.0 && 2.0 / sum_sq.sqrt() <= MITER_LIMIT {
+            let scale = 2.0 * half / sum_sq;
+            let miter = (point.0 + sum.0 * scale, point.1 + sum.1 * scale);
+            self.add_convex(&[point, p1, miter, p2]);
+        } else {
+            self.add_convex(&[point, p1, p2]);
+        }
+    }
+}
+
+/// Pixels from `start` to `end` (inclusive) in order
+fn line_pixels(start: Coord, end: Coord) -> Vec<Coord> {
+    let (dx, dy) = ((end.x - start.x).abs(), -(end.y - start.y).abs());
+    let (sx, sy) = ((end.x - start.x).signum(), (end.y - start.y).signum());
+    let mut error = dx + dy;
+    let mut current = start;
+    let mut pixels = vec![start];
+    while current != end {
+        let doubled = error * 2;
+        if doubled >= dy {
+            error += dy;
+            current.x += sx;
+        }
+        if doubled <= dx {
+            error += dx;
+            current.y += sy;
+        }
+        pixels.push(current);
+    }
+    pixels
+}
+
+/// Split `points` into the dashes of `dash`
+///
+/// Open paths are extended by half a pixel at each end (so that dashes cover whole pixels like
+/// [StrokePixels::add_dashed_pixels]), the dashes should be drawn without butt cap extensions
+fn dash_path(points: &[Point], closed: bool, dash: Dash) -> Vec<Vec<Point>> {
+    let mut points: Vec<Point> = points.to_vec();
+    points.dedup();
+    if points.len() < 2 || dash.on == 0 {
+        return vec![];
+    }
+    if closed {
+        if points.first() != points.last() {
+            points.push(points[0]);
+        }
+    } else {
+        let extend = |from: Point, to: Point| {
+            let len = (to.0 - from.0).hypot(to.1 - from.1);
+            (
+                to.0 + (to.0 - from.0) / len * 0.5,
+                to.1 + (to.1 - from.1) / len * 0.5,
+            )
+        };
+        let last = points.len() - 1;
+        points[0] = extend(points[1], points[0]);
+        points[last] = extend(points[last - 1], points[last]);
+    }
+    let (on, period) = (dash.on as f32, dash.period() as f32);
+    let mut phase = (dash.offset % dash.period()) as f32;
+    let mut dashes = vec![];
+    let mut current = vec![];
+    if phase < on {
+        current.push(points[0]);
+    }
+    for pair in points.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let len = (b.0 - a.0).hypot(b.1 - a.1);
+        let mut travelled = 0.0;
+        while travelled < len {
+            let is_on = phase < on;
+            let boundary = if is_on { on } else { period };
+            let step = (boundary - phase).min(len - travelled);
+            travelled += step;
+            phase += step;
+            let point = (
+                a.0 + (b.0 - a.0) * travelled / len,
+                a.1 + (b.1 - a.1) * travelled / len,
+            );
+            if is_on {
+                current.push(point);
+            }
+            if phase >= boundary - f32::EPSILON * period {
+                if is_on {
+                    dashes.push(std::mem::take(&mut current));
+                } else {
+                    current.push(point);
+                }
+                if phase >= period - f32::EPSILON * period {
+                    phase = 0.0;
+                }
+            }
+        }
+    }
+    dashes.push(current);
+    dashes.retain(|dash| dash.len() > 1);
+    dashes
+}
+
+impl Graphics<'_> {
+    fn stroke_pixels(&self) -> StrokePixels {
+        let offset = self.origin - self.translate;
+        StrokePixels::new((
+            offset.x,
+            offset.y,
+            offset.x + self.width as isize - 1,
+            offset.y + self.height as isize - 1,
+        ))
+    }
+
+    fn draw_stroke_pixels(&mut self, stroke: StrokePixels, color: Color) {
+        for (y, start_x, end_x) in stroke.into_spans() {
+            self.fill_span(y, start_x, end_x, color);
+        }
+    }
+
+    /// Draw a line from `start` to `end` using `style`
+    pub fn draw_line_with_style<P1: Into<Coord>, P2: Into<Coord>>(
+        &mut self,
+        start: P1,
+        end: P2,
+        color: Color,
+        style: StrokeStyle,
+    ) {
+        if style.is_thin() {
+            self.draw_line(start, end, color);
+        } else {
+            self.stroke_shape(
+                &Line::new(start.into(), end.into()).to_shape_box(),
+                color,
+                style,
+            );
+        }
+    }
+
+    /// Draw the outline of `shape` using `style`, this follows the transform (but the width
+    /// isn't scaled)
+    pub(crate) fn stroke_shape(&mut self, shape: &ShapeBox, color: Color, style: StrokeStyle) {
+        let mut stroke = self.stroke_pixels();
+        let half = style.width.max(1) as f32 / 2.0;
+        match shape {
+            ShapeBox::Circle(circle) if self.transform.is_identity() && style.dash.is_none() => {
+                let center = circle.center();
+                let radius = circle.radius() as f32;
+                stroke.add_ring(center, (radius, radius), half, |x, y| {
+                    (x - center.x as f32).hypot(y - center.y as f32) - radius
+                });
+            }
+            ShapeBox::Ellipse(ellipse)
+                if self.transform.is_identity()
+                    && style.dash.is_none()
+                    && ellipse.angle() == 0
+                    && ellipse.width() > 1
+                    && ellipse.height() > 1 =>
+            {
+                let center = ellipse.center();
+                let a = (ellipse.width() / 2) as f32;
+                let b = (ellipse.height() / 2) as f32;
+                stroke.add_ring(center, (a, b), half, |x, y| {
+                    let (x, y) = (x - center.x as f32, y - center.y as f32);
+                    let value = (x * x) / (a * a) + (y * y) / (b * b);
+                    let gradient = (2.0 * x / (a * a)).hypot(2.0 * y / (b * b));
+                    if gradient == 0.0 {
+                        -a.min(b)
+                    } else {
+                        (value - 1.0) / gradient
+                    }
+                });
+            }
+            _ => {
+                let points: Vec<Point> = outline_points(shape)
+                    .into_iter()
+                    .map(|p| self.transform.apply_f32(p))
+                    .collect();
+                stroke.add_styled_path(&points, !matches!(shape, ShapeBox::Line(_)), style);
+            }
+        }
+        self.draw_stroke_pixels(stroke, color);
+    }
+
+    /// Draw `paths` (already transformed) as a single stroke using `style`, the dash pattern
+    /// continues from the end of each path to the start of the next
+    pub(crate) fn stroke_paths(
+        &mut self,
+        paths: &[Vec<(f32, f32)>],
+        closed: bool,
+        color: Color,
+        mut style: StrokeStyle,
+    ) {
+        let mut stroke = self.stroke_pixels();
+        for points in paths {
+            let travelled = stroke.add_styled_path(points, closed, style);
+            if let Some(dash) = &mut style.dash {
+                dash.offset += travelled;
+            }
+        }
+        self.draw_stroke_pixels(stroke, color);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::make_image;
+    use crate::prelude::*;
+
+    fn rows(image: &Image) -> Vec<String> {
+        image
+            .pixels()
+            .chunks_exact(image.width())
+            .map(|row| {
+                row.iter()
+                    .map(|c| if c.a > 0 { '#' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn draw<F: FnOnce(&mut Graphics)>(width: usize, height: usize, method: F) -> Vec<String> {
+        rows(&make_image(width, height, method).unwrap())
+    }
+
+    #[test]
+    fn line_caps() {
+        let line = |cap: LineCap| {
+            draw(9, 5, |g| {
+                g.draw_line_with_style((2, 2), (6, 2), RED, StrokeStyle::new(3).with_cap(cap))
+            })
+        };
+        assert_eq!(
+            line(LineCap::Butt),
+            [
+                ".........",
+                "..#####..",
+                "..#####..",
+                "..#####..",
+                "........."
+            ]
+        );
+        assert_eq!(
+            line(LineCap::Square),
+            [
+                ".........",
+                ".#######.",
+                ".#######.",
+                ".#######.",
+                "........."
+            ]
+        );
+        let round = draw(13, 9, |g| {
+            let style = StrokeStyle::new(5).with_cap(LineCap::Round);
+            g.draw_line_with_style((4, 4), (8, 4), RED, style)
+        });
+        assert_eq!(
+            round,
+            [
+                ".............",
+                ".............",
+                "...#######...",
+                "..#########..",
+                "..#########..",
+                "..#########..",
+                "...#######...",
+                ".............",
+                ".............",
+            ]
+        );
+        assert_eq!(
+            draw(9, 6, |g| g.draw_line_with_style(
+                (2, 2),
+                (6, 2),
+                RED,
+                StrokeStyle::new(2)
+            )),
+            [
+                ".........",
+                "..#####..",
+                "..#####..",
+                ".........",
+                ".........",
+                "........."
+            ]
+        );
+    }
+
+    #[test]
+    fn thin_matches_stroke() {
+        let shapes = [
+            Rect::new((1, 1), (8, 6)).to_shape_box(),
+            Circle::new((5, 5), 4).to_shape_box(),
+            Triangle::new((1, 1), (9, 3), (3, 8)).to_shape_box(),
+        ];
+        for shape in shapes {
+            let thin = make_image(11, 11, |g| {
+                g.draw(&Drawable::from_obj(
+                    shape.clone(),
+                    stroke_with(RED, StrokeStyle::new(1)),
+                ))
+            });
+            let stroke = make_image(11, 11, |g| {
+                g.draw(&Drawable::from_obj(shape.clone(), stroke(RED)))
+            });
+            assert_eq!(thin.unwrap(), stroke.unwrap());
+        }
+    }
+
+    #[test]
+    fn joins() {
+        let rect = |join: LineJoin| {
+            draw(9, 9, |g| {
+                g.draw_rect(
+                    Rect::new((2, 2), (6, 6)),
+                    stroke_with(RED, StrokeStyle::new(3).with_join(join)),
+                )
+            })
+        };
+        let miter = rect(LineJoin::Miter);
+        assert_eq!(miter[0], ".........");
+        assert_eq!(miter[1], ".#######.");
+        assert_eq!(miter[4], ".###.###.");
+        assert_eq!(miter[7], ".#######.");
+        let bevel = rect(LineJoin::Bevel);
+        assert_eq!(bevel[1], "..#####..");
+        assert_eq!(bevel[2], ".#######.");
+        assert_eq!(bevel[7], "..#####..");
+    }
+
+    #[test]
+    fn thick_shapes() {
+        let circle = draw(13, 13, |g| {
+            g.draw_circle(
+                Circle::new((6, 6), 4),
+                stroke_with(RED, StrokeStyle::new(3)),
+            )
+        });
+        assert_eq!(circle[6], ".###.....###.");
+        assert_eq!(circle[0], ".............");
+
+        let polyline = Polyline::start((1, 1), RED)
+            .add_line_to((8, 1))
+            .unwrap()
+            .add_line_to((8, 8))
+            .unwrap()
+            .with_stroke_style(StrokeStyle::new(2));
+        let lines = draw(11, 11, |g| g.draw(&polyline));
+        // even widths extend up and left of the outline
+        assert_eq!(lines[0], ".########..");
+        assert_eq!(lines[1], ".########..");
+        assert_eq!(lines[2], ".......##..");
+        assert_eq!(lines[8], ".......##..");
+        assert_eq!(lines[9], "...........");
+
+        let transparent = make_image(9, 9, |g| {
+            g.draw_rect(
+                Rect::new((2, 2), (6, 6)),
+                stroke_with(RED.with_alpha(100), StrokeStyle::new(3)),
+            )
+        })
+        .unwrap();
+        assert_eq!(transparent.get_pixel(1, 1), transparent.get_pixel(2, 2));
+    }
+
+    #[test]
+    fn dashes() {
+        let line = |dash: Dash, width: usize| {
+            draw(12, 3, |g| {
+                g.draw_line_with_style(
+                    (1, 1),
+                    (10, 1),
+                    RED,
+                    StrokeStyle::new(width).with_dash(dash),
+                )
+            })[1]
+                .clone()
+        };
+        assert_eq!(line(Dash::new(2, 2), 1), ".##..##..##.");
+        assert_eq!(line(Dash::new(2, 2).with_offset(1), 1), ".#..##..##..");
+        assert_eq!(line(Dash::dotted(), 1), ".#.#.#.#.#..");
+        assert_eq!(line(Dash::new(3, 2), 3), ".###..###...");
+        assert_eq!(line(Dash::new(3, 0), 1), ".##########.");
+
+        let rect = draw(6, 6, |g| {
+            let style = StrokeStyle::new(1).with_dash(Dash::new(3, 1));
+            g.draw_rect(Rect::new((1, 1), (4, 4)), stroke_with(RED, style))
+        });
+        assert_eq!(
+            rect,
+            ["......", ".###..", "....#.", ".#..#.", ".##.#.", "......"]
+        );
+    }
+
+    #[test]
+    fn dashes_continue_across_segments() {
+        let style = StrokeStyle::new(1).with_dash(Dash::new(2, 1));
+        let polyline = Polyline::start((1, 1), RED)
+            .add_line_to((4, 1))
+            .unwrap()
+            .add_line_to((4, 5))
+            .unwrap()
+            .with_stroke_style(style);
+        let lines = draw(6, 7, |g| g.draw(&polyline));
+        assert_eq!(
+            lines,
+            ["......", ".##.#.", "....#.", "......", "....#.", "....#.", "......"]
+        );
+
+        let arc = Polyline::start((0, 5), RED)
+            .add_arc_around((5, 5), 5, 270, 90)
+            .unwrap();
+        let solid = make_image(8, 8, |g| {
+            g.draw(&arc.with_stroke_style(StrokeStyle::new(1)))
+        });
+        let dashed = make_image(8, 8, |g| g.draw(&arc.with_stroke_style(style)));
+        let (solid, dashed) = (solid.unwrap(), dashed.unwrap());
+        let count = |image: &Image| image.pixels().iter().filter(|c| c.a > 0).count();
+        assert!(count(&dashed) < count(&solid));
+        assert!(count(&dashed) >= count(&solid) / 2);
+        for (solid, dashed) in solid.pixels().iter().zip(dashed.pixels()) {
+            assert!(dashed.a == 0 || solid.a > 0);
+        }
+    }
+
+    #[test]
+    fn wide_polyline_matches_thin() {
+        let polyline = Polyline::start((1, 1), RED)
+            .add_line_to((4, 1))
+            .unwrap()
+            .add_arc_around((10, 10), 4, 0, 90)
+            .unwrap()
+            .add_line_to((14, 16))
+            .unwrap();
+        let draw = |style: StrokeStyle| {
+            make_image(18, 18, |g| {
+                g.draw(&polyline.clone().with_stroke_style(style))
+            })
+            .unwrap()
+        };
+        let thin = draw(StrokeStyle::new(1));
+        let wide = draw(StrokeStyle::new(3));
+        let dashed = draw(StrokeStyle::new(1).with_dash(Dash::new(3, 1)));
+        for (i, thin) in thin.pixels().iter().enumerate() {
+            assert!(thin.a == 0 || wide.pixels()[i].a > 0, "{i}");
+            assert!(thin.a > 0 || dashed.pixels()[i].a == 0, "{i}");
+        }
+        // the arc isn't connected to the first line
+        assert_eq!(wide.get_pixel(7, 3).a, 0);
+    }
+
+    #[test]
+    fn clipped_to_buffer() {
+        let draw = |size: usize, translate: Coord| {
+            make_image(size, size, |g| {
+                g.set_translate(translate);
+                let style = StrokeStyle::new(5);
+                g.draw_circle(Circle::new((10, 10), 8), stroke_with(RED, style));
+                g.draw_line_with_style((0, 19), (19, 0), BLUE, style);
+            })
+            .unwrap()
+        };
+        let full = draw(20, coord!(0, 0));
+        let part = draw(8, coord!(-6, -3));
+        for y in 0..8 {
+            for x in 0..8 {
+                assert_eq!(part.get_pixel(x, y), full.get_pixel(x + 6, y + 3));
+            }
+        }
+    }
+}
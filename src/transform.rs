@@ -0,0 +1,267 @@
+use graphics_shapes::coord::Coord;
+use graphics_shapes::prelude::*;
+use graphics_shapes::shape_box::ShapeBox;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+/// 2D affine transform, stored as the matrix
+/// ```text
+/// | a c e |
+/// | b d f |
+/// | 0 0 1 |
+/// ```
+/// so a point is transformed with `x' = a*x + c*y + e` and `y' = b*x + d*y + f`
+///
+/// Transforms are combined with [then](Transform::then), for example to zoom in on (100, 100)
+/// ```
+///# use buffer_graphics_lib::prelude::*;
+/// let camera = Transform::translation(-100.0, -100.0)
+///     .then(Transform::scale(2.0, 2.0))
+///     .then(Transform::translation(100.0, 100.0));
+/// assert_eq!(camera.apply((150, 100)), coord!(200, 100));
+/// ```
+///
+/// # Shapes and images
+/// Shapes (and lines and polylines) are made of points at pixel centers, so only their points are
+/// transformed and then drawn as usual. Images cover whole pixels, so their area is transformed.
+/// This means that with `Transform::scale(2.0, 2.0)` a rect from (0,0) to (1,1) becomes a rect
+/// from (0,0) to (2,2) and covers 3x3 pixels, but a 2x2 image covers 4x4 pixels
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::IDENTITY
+    }
+}
+
+impl Transform {
+    /// Transform that doesn't change anything
+    pub const IDENTITY: Transform = Transform::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+    pub const fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    /// Move points by `x`, `y`
+    pub const fn translation(x: f32, y: f32) -> Self {
+        Transform::new(1.0, 0.0, 0.0, 1.0, x, y)
+    }
+
+    /// Scale points away from (0,0)
+    pub const fn scale(x: f32, y: f32) -> Self {
+        Transform::new(x, 0.0, 0.0, y, 0.0, 0.0)
+    }
+
+    /// Rotate points clockwise around (0,0)
+    pub fn rotation(degrees: f32) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Transform::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    /// Skew points horizontally by `x_degrees` and vertically by `y_degrees`
+    pub fn skew(x_degrees: f32, y_degrees: f32) -> Self {
+        Transform::new(
+            1.0,
+            y_degrees.to_radians().tan(),
+            x_degrees.to_radians().tan(),
+            1.0,
+            0.0,
+            0.0,
+        )
+    }
+
+    /// Rotate points clockwise around `center`
+    pub fn rotation_around<P: Into<Coord>>(degrees: f32, center: P) -> Self {
+        let center = center.into();
+        let (x, y) = (center.x as f32, center.y as f32);
+        Transform::translation(-x, -y)
+            .then(Transform::rotation(degrees))
+            .then(Transform::translation(x, y))
+    }
+
+    /// Scale points away from `center`
+    pub fn scale_around<P: Into<Coord>>(x_scale: f32, y_scale: f32, center: P) -> Self {
+        let center = center.into();
+        let (x, y) = (center.x as f32, center.y as f32);
+        Transform::translation(-x, -y)
+            .then(Transform::scale(x_scale, y_scale))
+            .then(Transform::translation(x, y))
+    }
+}
+
+impl Transform {
+    /// Returns a transform that applies `self` and then `next`
+    pub fn then(&self, next: Transform) -> Transform {
+        Transform::new(
+            next.a * self.a + next.c * self.b,
+            next.b * self.a + next.d * self.b,
+            next.a * self.c + next.c * self.d,
+            next.b * self.c + next.d * self.d,
+            next.a * self.e + next.c * self.f + next.e,
+            next.b * self.e + next.d * self.f + next.f,
+        )
+    }
+
+    /// Returns the transform that undoes this one, or `None` if this transform collapses
+    /// points onto a line or point (i.e. has a scale of 0)
+    pub fn inverse(&self) -> Option<Transform> {
+        let det = self.a * self.d - self.b * self.c;
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        let a = self.d / det;
+        let b = -self.b / det;
+        let c = -self.c / det;
+        let d = self.a / det;
+        Some(Transform::new(
+            a,
+            b,
+            c,
+            d,
+            -(a * self.e + c * self.f),
+            -(b * self.e + d * self.f),
+        ))
+    }
+
+    #[inline]
+    pub fn is_identity(&self) -> bool {
+        *self == Transform::IDENTITY
+    }
+
+    #[inline]
+    pub fn apply_f32(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
+        )
+    }
+
+    /// Transform `point`, rounding to the nearest pixel
+    #[inline]
+    pub fn apply<P: Into<Coord>>(&self, point: P) -> Coord {
+        let point = point.into();
+        let (x, y) = self.apply_f32((point.x as f32, point.y as f32));
+        Coord::new(x.round() as isize, y.round() as isize)
+    }
+}
+
+/// Outline of a shape after being transformed
+pub(crate) enum TransformedShape {
+    Line(Coord, Coord),
+    Polygon(Vec<Coord>),
+}
+
+impl TransformedShape {
+    pub(crate) fn new(shape: &ShapeBox, transform: &Transform) -> Self {
+        if let ShapeBox::Line(line) = shape {
+            return TransformedShape::Line(
+                transform.apply(line.start()),
+                transform.apply(line.end()),
+            );
+        }
+        TransformedShape::Polygon(
+            outline_points(shape)
+                .into_iter()
+                .map(|p| {
+                    let (x, y) = transform.apply_f32(p);
+                    Coord::new(x.round() as isize, y.round() as isize)
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Points around the outline of `shape` (in order), circles and ellipses are approximated
+pub(crate) fn outline_points(shape: &ShapeBox) -> Vec<(f32, f32)> {
+    match shape {
+        ShapeBox::Line(line) => [line.start(), line.end()]
+            .iter()
+            .map(|p| (p.x as f32, p.y as f32))
+            .collect(),
+        ShapeBox::Rect(rect) => rect
+            .as_polygon()
+            .points()
+            .iter()
+            .map(|p| (p.x as f32, p.y as f32))
+            .collect(),
+        ShapeBox::Triangle(triangle) => triangle
+            .points()
+            .iter()
+            .map(|p| (p.x as f32, p.y as f32))
+            .collect(),
+        ShapeBox::Polygon(polygon) => polygon.fpoints().to_vec(),
+        ShapeBox::Circle(circle) => {
+            let radius = circle.radius() as f32;
+            discretise_ellipse(circle.center(), radius, radius, 0.0)
+        }
+        ShapeBox::Ellipse(ellipse) => discretise_ellipse(
+            ellipse.center(),
+            (ellipse.width() / 2) as f32,
+            (ellipse.height() / 2) as f32,
+            ellipse.angle() as f32,
+        ),
+    }
+}
+
+/// Points around an ellipse, with enough points that the outline looks smooth at this size
+fn discretise_ellipse(center: Coord, rx: f32, ry: f32, degrees: f32) -> Vec<(f32, f32)> {
+    let segments = ((rx + ry) * 2.0).clamp(8.0, 720.0) as usize;
+    let rotation = Transform::rotation(degrees);
+    (0..segments)
+        .map(|i| {
+            let angle = TAU * i as f32 / segments as f32;
+            let (x, y) = rotation.apply_f32((rx * angle.cos(), ry * angle.sin()));
+            (center.x as f32 + x, center.y as f32 + y)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn basic_transforms() {
+        assert_eq!(Transform::IDENTITY.apply((3, 4)), Coord::new(3, 4));
+        assert_eq!(
+            Transform::translation(2.0, -1.0).apply((3, 4)),
+            Coord::new(5, 3)
+        );
+        assert_eq!(Transform::scale(2.0, 3.0).apply((3, 4)), Coord::new(6, 12));
+        assert_eq!(Transform::rotation(90.0).apply((10, 0)), Coord::new(0, 10));
+        assert_eq!(
+            Transform::skew(45.0, 0.0).apply((0, 10)),
+            Coord::new(10, 10)
+        );
+        assert_eq!(
+            Transform::rotation_around(180.0, (10, 10)).apply((5, 10)),
+            Coord::new(15, 10)
+        );
+    }
+
+    #[test]
+    fn then_and_inverse() {
+        let transform = Transform::scale(2.0, 2.0)
+            .then(Transform::rotation(30.0))
+            .then(Transform::translation(5.0, 7.0));
+        let inverse = transform.inverse().unwrap();
+        assert_eq!(inverse.apply(transform.apply((13, -4))), Coord::new(13, -4));
+        assert_eq!(
+            Transform::scale(2.0, 1.0)
+                .then(Transform::translation(1.0, 0.0))
+                .apply((3, 3)),
+            Coord::new(7, 3)
+        );
+        assert!(Transform::scale(0.0, 1.0).inverse().is_none());
+    }
+}
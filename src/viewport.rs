@@ -0,0 +1,137 @@
+use crate::clipping::Clip;
+use crate::{Graphics, GraphicsError};
+use graphics_shapes::coord::Coord;
+
+impl Graphics<'_> {
+    /// Create a [Graphics] that draws to a `width`x`height` region of this buffer starting at `xy`
+    ///
+    /// The returned graphics has its own width, height, translate and clip and (0,0) is `xy` in
+    /// this buffer, nothing drawn to it can affect pixels outside of the region. No pixels are
+    /// copied, the region is borrowed until the returned graphics is dropped
+    ///
+    /// `xy` is in buffer coordinates (see [origin](Graphics::origin)), i.e. the translate, transform and
+    /// clip of this graphics are ignored
+    ///
+    /// # Usage
+    /// ```
+    ///# use buffer_graphics_lib::prelude::*;
+    ///# fn doc(graphics: &mut Graphics) -> Result<(), GraphicsError> {
+    /// let mut widget = graphics.viewport((20, 20), 100, 30)?;
+    /// widget.clear(BLUE);
+    /// widget.draw_text("Button", TextPos::px(coord!(4, 4)), WHITE);
+    ///# Ok(())
+    ///# }
+    /// ```
+    ///
+    /// # Errors
+    /// * `GraphicsError::ViewportOutOfBounds` if the region isn't completely inside this buffer
+    pub fn viewport<P: Into<Coord>>(
+        &mut self,
+        xy: P,
+        width: usize,
+        height: usize,
+    ) -> Result<Graphics<'_>, GraphicsError> {
+        let xy = xy.into() - self.origin;
+        if xy.x < 0
+            || xy.y < 0
+            || xy.x as usize + width > self.width
+            || xy.y as usize + height > self.height
+        {
+            return Err(GraphicsError::ViewportOutOfBounds(
+                xy.x,
+                xy.y,
+                width,
+                height,
+                self.width,
+                self.height,
+            ));
+        }
+        if let Some(dirty) = &mut self.dirty {
+            dirty.mark_region(xy.x as usize, xy.y as usize, width, height);
+        }
+        let start = self.index(xy.x as usize, xy.y as usize);
+        let end = if width == 0 || height == 0 {
+            start
+        } else {
+            self.index(xy.x as usize + width, xy.y as usize + height - 1)
+        };
+        Ok(Graphics {
+            buffer: self.buffer.slice_mut(start, end),
+            width,
+            height,
+            stride: self.stride,
+            origin: Coord::default(),
+            translate: Coord::default(),
+            transform: Default::default(),
+            clip: Clip::new(width, height),
+            blend_mode: Default::default(),
+            composite_mode: Default::default(),
+            anti_aliasing: false,
+            custom_font: self.custom_font.clone(),
+            state_stack: vec![],
+            dirty: None,
+            index_method: self.index_method,
+            clear_method: self.clear_method,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::*;
+
+    #[test]
+    fn viewport_is_contained() {
+        let mut buf_u8 = Graphics::create_buffer_u8(6, 5);
+        let mut buf_rgba = Graphics::create_buffer_u32(6, 5);
+        let mut buf_argb = Graphics::create_buffer_u32(6, 5);
+        let graphics = [
+            Graphics::new_u8_rgba(&mut buf_u8, 6, 5).unwrap(),
+            Graphics::new_u32_rgba(&mut buf_rgba, 6, 5).unwrap(),
+            Graphics::new_u32_argb(&mut buf_argb, 6, 5).unwrap(),
+        ];
+        for mut graphics in graphics {
+            graphics.clear(BLACK);
+            {
+                let mut view = graphics.viewport((1, 2), 3, 2).unwrap();
+                assert_eq!(view.width(), 3);
+                assert_eq!(view.height(), 2);
+                view.clear(BLUE);
+                view.set_pixel(0, 0, RED);
+                view.draw_line((-5, 1), (10, 1), GREEN);
+                assert_eq!(view.get_pixel(0, 0, false), Some(RED));
+                assert_eq!(view.get_pixel(3, 0, false), None);
+                let image = view.copy_to_image();
+                assert_eq!(image.pixels(), &[RED, BLUE, BLUE, GREEN, GREEN, GREEN]);
+            }
+            for y in 0..5 {
+                for x in 0..6 {
+                    let expected = match (x, y) {
+                        (1, 2) => RED,
+                        (1..=3, 2) => BLUE,
+                        (1..=3, 3) => GREEN,
+                        _ => BLACK,
+                    };
+                    assert_eq!(graphics.get_pixel(x, y, false), Some(expected), "{x},{y}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn viewport_bounds() {
+        let mut buf = Graphics::create_buffer_u8(6, 5);
+        let mut graphics = Graphics::new_u8_rgba(&mut buf, 6, 5).unwrap();
+        assert!(graphics.viewport((0, 0), 6, 5).is_ok());
+        assert!(graphics.viewport((5, 4), 1, 1).is_ok());
+        assert!(graphics.viewport((5, 4), 2, 1).is_err());
+        assert!(graphics.viewport((-1, 0), 2, 1).is_err());
+        let mut view = graphics.viewport((1, 1), 4, 3).unwrap();
+        let mut nested = view.viewport((1, 1), 2, 2).unwrap();
+        nested.clear(RED);
+        drop(nested);
+        assert_eq!(view.get_pixel(1, 1, false), Some(RED));
+        assert_eq!(graphics.get_pixel(2, 2, false), Some(RED));
+        assert_eq!(graphics.get_pixel(4, 4, false), Some(TRANSPARENT));
+    }
+}